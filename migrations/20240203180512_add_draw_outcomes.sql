@@ -0,0 +1,10 @@
+-- Allow games to end in automatically detected draws
+ALTER TABLE games DROP CONSTRAINT outcome_check;
+ALTER TABLE games ADD CONSTRAINT outcome_check CHECK (outcome IN (
+    'checkmate',
+    'stalemate',
+    'resignation',
+    'threefold_repetition',
+    'fifty_move_rule',
+    'insufficient_material'
+));
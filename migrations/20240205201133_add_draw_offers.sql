@@ -0,0 +1,15 @@
+-- Track a pending draw offer by the player who made it
+ALTER TABLE games ADD COLUMN draw_offer VARCHAR(32) DEFAULT NULL;
+ALTER TABLE games ADD CONSTRAINT draw_offer_check CHECK (draw_offer IN ('white', 'black'));
+
+-- Allow games to end in a draw by agreement
+ALTER TABLE games DROP CONSTRAINT outcome_check;
+ALTER TABLE games ADD CONSTRAINT outcome_check CHECK (outcome IN (
+    'checkmate',
+    'stalemate',
+    'resignation',
+    'threefold_repetition',
+    'fifty_move_rule',
+    'insufficient_material',
+    'agreement'
+));
@@ -0,0 +1,4 @@
+-- Secret tokens held by the players seated on each side of a game.
+--  NULL means the seat is still open.
+ALTER TABLE games ADD COLUMN white_token UUID DEFAULT NULL;
+ALTER TABLE games ADD COLUMN black_token UUID DEFAULT NULL;
@@ -0,0 +1,22 @@
+-- Registered players
+CREATE TABLE IF NOT EXISTS users (
+    id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+    username VARCHAR(32) UNIQUE NOT NULL,
+    -- Argon2 hash in PHC string format
+    password_hash TEXT NOT NULL,
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Server side login sessions -- the id is handed to the browser as a cookie
+CREATE TABLE IF NOT EXISTS sessions (
+    id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP + INTERVAL '30 days'
+);
+
+CREATE INDEX IF NOT EXISTS idx_sessions_user_id ON sessions(user_id);
+
+-- Record which users, if any, are seated in a game
+ALTER TABLE games ADD COLUMN white_user_id UUID REFERENCES users(id) ON DELETE SET NULL DEFAULT NULL;
+ALTER TABLE games ADD COLUMN black_user_id UUID REFERENCES users(id) ON DELETE SET NULL DEFAULT NULL;
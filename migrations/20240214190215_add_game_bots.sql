@@ -0,0 +1,8 @@
+-- Games can be played against one of pleco's built-in search bots
+ALTER TABLE games ADD COLUMN bot VARCHAR(32) DEFAULT NULL;
+ALTER TABLE games ADD CONSTRAINT bot_check CHECK (bot IN ('minimax', 'alpha_beta', 'jamboree', 'iterative_parallel_mvv'));
+-- How many plies deep the bot searches
+ALTER TABLE games ADD COLUMN bot_depth SMALLINT DEFAULT NULL;
+-- Which side the bot is playing
+ALTER TABLE games ADD COLUMN bot_player VARCHAR(32) DEFAULT NULL;
+ALTER TABLE games ADD CONSTRAINT bot_player_check CHECK (bot_player IN ('white', 'black'));
@@ -0,0 +1,5 @@
+-- Record the move that was played alongside the position it led to
+--  Existing moves are backfilled by the app on startup, since deriving them from
+--  consecutive positions needs a move generator
+ALTER TABLE moves ADD COLUMN uci VARCHAR;
+ALTER TABLE moves ADD COLUMN san VARCHAR;
@@ -0,0 +1,22 @@
+-- Games can be played on the clock, e.g. '5+3' (minutes plus seconds of increment per
+--  move) or '3d' (days per move for correspondence games). Untimed games have none.
+ALTER TABLE games ADD COLUMN time_control VARCHAR(32) DEFAULT NULL;
+ALTER TABLE games ADD CONSTRAINT time_control_check CHECK (time_control ~ '^([0-9]+\+[0-9]+|[0-9]+d)$');
+-- Each side's remaining time as of the last move
+ALTER TABLE games ADD COLUMN white_clock_ms BIGINT DEFAULT NULL;
+ALTER TABLE games ADD COLUMN black_clock_ms BIGINT DEFAULT NULL;
+-- When the clock of the player to move started running, null until the first move
+ALTER TABLE games ADD COLUMN clock_started_at TIMESTAMP WITH TIME ZONE DEFAULT NULL;
+
+-- Allow games to be lost on time
+ALTER TABLE games DROP CONSTRAINT outcome_check;
+ALTER TABLE games ADD CONSTRAINT outcome_check CHECK (outcome IN (
+    'checkmate',
+    'stalemate',
+    'resignation',
+    'threefold_repetition',
+    'fifty_move_rule',
+    'insufficient_material',
+    'agreement',
+    'timeout'
+));
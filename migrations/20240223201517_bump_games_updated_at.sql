@@ -0,0 +1,24 @@
+-- Keep updated_at current on every change to a game -- moves, resignations, draw offers,
+--  seats and completions all update the games row
+CREATE OR REPLACE FUNCTION set_updated_at() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.updated_at = CURRENT_TIMESTAMP;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER games_set_updated_at
+BEFORE UPDATE ON games
+FOR EACH ROW
+EXECUTE FUNCTION set_updated_at();
+
+-- Catch up games played before updated_at was maintained
+UPDATE games
+SET updated_at = latest.created_at
+FROM (
+    SELECT game_id, MAX(created_at) as created_at
+    FROM moves
+    GROUP BY game_id
+) as latest
+WHERE latest.game_id = games.id
+AND latest.created_at > games.updated_at;
@@ -0,0 +1,15 @@
+-- Move requests that carried an idempotency key, so repeats of one can be answered with
+--  the original outcome instead of being played again. Kept for a while, then cleaned up
+--  by the app.
+CREATE TABLE IF NOT EXISTS move_requests (
+    game_id UUID NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    idempotency_key VARCHAR(255) NOT NULL,
+    uci_move VARCHAR(8) NOT NULL,
+    resign BOOLEAN NOT NULL,
+    -- The ply the game was at once the request was handled
+    ply INTEGER NOT NULL,
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (game_id, idempotency_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_move_requests_created_at ON move_requests(created_at);
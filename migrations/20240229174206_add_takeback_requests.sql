@@ -0,0 +1,3 @@
+-- Track a pending takeback request by the player who made it
+ALTER TABLE games ADD COLUMN takeback_request VARCHAR(32) DEFAULT NULL;
+ALTER TABLE games ADD CONSTRAINT takeback_request_check CHECK (takeback_request IN ('white', 'black'));
@@ -0,0 +1,3 @@
+-- Store when the clock started like every other timestamp, in UTC without a time zone
+ALTER TABLE games ALTER COLUMN clock_started_at TYPE TIMESTAMP WITHOUT TIME ZONE
+    USING clock_started_at AT TIME ZONE 'UTC';
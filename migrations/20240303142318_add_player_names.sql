@@ -0,0 +1,4 @@
+-- Names of players who don't have an account here, e.g. from an imported game's PGN tags.
+--  They're only shown, never used to pick out a user.
+ALTER TABLE games ADD COLUMN white_name TEXT DEFAULT NULL;
+ALTER TABLE games ADD COLUMN black_name TEXT DEFAULT NULL;
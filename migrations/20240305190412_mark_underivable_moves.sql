@@ -0,0 +1,6 @@
+-- Moves the notation backfill couldn't find a legal move for, so it stops looking at
+--  them on every startup. Whatever's left for it to do is found through the index.
+ALTER TABLE moves ADD COLUMN notation_underivable BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX IF NOT EXISTS idx_moves_missing_notation ON moves(game_id)
+    WHERE uci IS NULL AND NOT notation_underivable;
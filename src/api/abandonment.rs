@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+use crate::api::games::watch_game_sse::GameUpdateStream;
+use crate::api::models::{ApiGameBoard, ApiViewer};
+use crate::api::templates::GameBoardTemplate;
+use crate::database::models::{GameBoard, GameError};
+use crate::AppState;
+
+/// How often to look for stale games
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Mark games nobody has played on for `idle` as abandoned in the background, and push the
+///  result to anyone still watching.
+pub fn spawn_sweeper(state: AppState, tx: GameUpdateStream, idle: Duration) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = sweep(&state, &tx, idle).await {
+                tracing::error!("failed to sweep abandoned games: {}", e);
+            }
+        }
+    });
+}
+
+async fn sweep(state: &AppState, tx: &GameUpdateStream, idle: Duration) -> Result<(), SweepError> {
+    let mut conn = state.database().begin().await?;
+    let abandoned = GameBoard::abandon_stale(&mut conn, idle.as_secs() as i64).await?;
+    let mut updates = Vec::with_capacity(abandoned.len());
+    for game_id in abandoned {
+        let api_game_board = ApiGameBoard::from(GameBoard::latest(&mut conn, game_id).await?);
+        updates.push((game_id, api_game_board));
+    }
+    conn.commit().await?;
+
+    for (game_id, api_game_board) in updates {
+        tracing::info!("abandoned stale game: game_id={}", game_id);
+        tx.send(
+            game_id,
+            GameBoardTemplate {
+                api_game_board,
+                viewer: ApiViewer::default(),
+            },
+        );
+    }
+    Ok(())
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SweepError {
+    #[error("sqlx error: {0}")]
+    Sqlx(#[from] sqlx::Error),
+    #[error("game error: {0}")]
+    Game(#[from] GameError),
+}
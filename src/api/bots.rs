@@ -0,0 +1,74 @@
+use sqlx::types::Uuid;
+
+use crate::api::games::watch_game_sse::GameUpdateStream;
+use crate::api::models::{ApiGameBoard, ApiViewer};
+use crate::api::templates::GameBoardTemplate;
+use crate::database::models::{BotSeat, GameBoard, GameError, GamePlayer};
+use crate::AppState;
+
+/// If it's a bot's turn in a game, search for its reply in the background, play it, and
+///  push the result to anyone watching
+pub fn spawn_reply(state: AppState, tx: GameUpdateStream, game_id: Uuid) {
+    tokio::spawn(async move {
+        if let Err(e) = reply(&state, &tx, game_id).await {
+            tracing::error!("bot failed to reply: game_id={} | {}", game_id, e);
+        }
+    });
+}
+
+async fn reply(state: &AppState, tx: &GameUpdateStream, game_id: Uuid) -> Result<(), BotError> {
+    // The board can change while the bot is thinking, e.g. by a takeback. Its move is then
+    //  stale, so it thinks again about the board as it is now.
+    loop {
+        let (bot_seat, board) = {
+            let mut conn = state.database().acquire().await?;
+            let bot_seat = match BotSeat::read(&mut conn, game_id).await? {
+                Some(bot_seat) => bot_seat,
+                None => return Ok(()),
+            };
+            let game_board = GameBoard::latest(&mut conn, game_id).await?;
+            if game_board.check_playable().is_err()
+                || GamePlayer::from(game_board.board().turn()) != bot_seat.player
+            {
+                return Ok(());
+            }
+            (bot_seat, game_board.board().clone())
+        };
+
+        // Searching is CPU bound, so keep it off the async runtime
+        let (bot, depth) = (bot_seat.bot, bot_seat.depth);
+        let ply = board.moves_played();
+        let uci_move = tokio::task::spawn_blocking(move || bot.best_move(&board, depth)).await?;
+
+        // The move is only played if nothing else was while the bot was thinking
+        let mut conn = state.database().begin().await?;
+        let token = Some(bot_seat.token);
+        match GameBoard::make_move(&mut conn, game_id, token, Some(ply), &uci_move, false).await {
+            // Running out of time while thinking still ends the game
+            Ok(()) | Err(GameError::TimedOut(_)) => {}
+            Err(GameError::StaleMove(_, _)) => continue,
+            Err(e) => return Err(e.into()),
+        }
+        let api_game_board = ApiGameBoard::from(GameBoard::latest(&mut conn, game_id).await?);
+        conn.commit().await?;
+
+        tx.send(
+            game_id,
+            GameBoardTemplate {
+                api_game_board,
+                viewer: ApiViewer::default(),
+            },
+        );
+        return Ok(());
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum BotError {
+    #[error("sqlx error: {0}")]
+    Sqlx(#[from] sqlx::Error),
+    #[error("game error: {0}")]
+    Game(#[from] GameError),
+    #[error("search task failed: {0}")]
+    Search(#[from] tokio::task::JoinError),
+}
@@ -0,0 +1,118 @@
+use std::time::Duration;
+
+use time::OffsetDateTime;
+
+use crate::api::games::watch_game_sse::GameUpdateStream;
+use crate::api::models::{ApiGameBoard, ApiViewer};
+use crate::api::templates::GameBoardTemplate;
+use crate::database::models::{GameBoard, GameError};
+use crate::AppState;
+
+/// How often to look for players who have run out of time
+const FLAG_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Flag players whose time runs out in the background, so games on the clock end even if
+///  nobody tries to move, and push the result to anyone watching
+pub fn spawn_flagger(state: AppState, tx: GameUpdateStream) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(FLAG_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = flag(&state, &tx, OffsetDateTime::now_utc()).await {
+                tracing::error!("failed to flag timeouts: {}", e);
+            }
+        }
+    });
+}
+
+async fn flag(
+    state: &AppState,
+    tx: &GameUpdateStream,
+    now: OffsetDateTime,
+) -> Result<(), FlagError> {
+    let candidates = {
+        let mut conn = state.database().acquire().await?;
+        GameBoard::timeout_candidates(&mut conn, now).await?
+    };
+
+    // Each game is only held while it's flagged, so moves in other games don't wait on it
+    for game_id in candidates {
+        let mut conn = state.database().begin().await?;
+        if !GameBoard::flag_timeout(&mut conn, game_id, now).await? {
+            continue;
+        }
+        let api_game_board = ApiGameBoard::from(GameBoard::latest(&mut conn, game_id).await?);
+        conn.commit().await?;
+
+        tx.send(
+            game_id,
+            GameBoardTemplate {
+                api_game_board,
+                viewer: ApiViewer::default(),
+            },
+        );
+    }
+    Ok(())
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FlagError {
+    #[error("sqlx error: {0}")]
+    Sqlx(#[from] sqlx::Error),
+    #[error("game error: {0}")]
+    Game(#[from] GameError),
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+
+    use crate::database::models::{Game, GamePlayer, GameStatus, NewGame, TimeControl};
+
+    #[sqlx::test]
+    async fn moving_in_time_beats_a_racing_flag(pool: PgPool) {
+        let time_control = TimeControl::try_from("5+0").unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        let game = NewGame::create(&mut conn, Some(time_control))
+            .await
+            .unwrap();
+        let game_id = game.id();
+        let white = Game::claim_seat(&mut conn, game_id, GamePlayer::White, None, None)
+            .await
+            .unwrap();
+        let black = Game::claim_seat(&mut conn, game_id, GamePlayer::Black, None, None)
+            .await
+            .unwrap();
+        GameBoard::make_move(&mut conn, game_id, Some(white), None, "e2e4", false)
+            .await
+            .unwrap();
+
+        // The flagger picks the game out at a time black's minute has run out by...
+        sqlx::query("UPDATE games SET black_clock_ms = 60000 WHERE id = $1")
+            .bind(game_id)
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        let now = OffsetDateTime::now_utc() + time::Duration::minutes(2);
+        let candidates = GameBoard::timeout_candidates(&mut conn, now).await.unwrap();
+        assert_eq!(candidates, vec![game_id]);
+
+        // ...but black replies before it gets to flag it, leaving white minutes to spare
+        GameBoard::make_move(&mut conn, game_id, Some(black), None, "e7e5", false)
+            .await
+            .unwrap();
+        let flagged = GameBoard::flag_timeout(&mut conn, game_id, now)
+            .await
+            .unwrap();
+        assert!(!flagged);
+
+        let game_board = GameBoard::latest(&mut conn, game_id).await.unwrap();
+        assert_eq!(*game_board.status(), GameStatus::Active);
+        assert_eq!(
+            game_board.moves(),
+            [Some("e4".to_string()), Some("e5".to_string())]
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension,
+};
+use axum_extra::extract::cookie::CookieJar;
+use sqlx::types::Uuid;
+
+use crate::api::models::{ApiGameBoard, ApiViewer};
+use crate::api::seats::{seat_cookie, seat_token};
+use crate::api::session::CurrentUser;
+use crate::api::templates::GameBoardTemplate;
+use crate::database::models::{Game, GameBoard, GameError, GamePlayer};
+use crate::AppState;
+
+use super::watch_game_sse::GameUpdateStream;
+
+pub async fn handler(
+    State(state): State<AppState>,
+    Extension(tx): Extension<GameUpdateStream>,
+    Path((game_id, player)): Path<(Uuid, String)>,
+    user: Option<CurrentUser>,
+    jar: CookieJar,
+) -> Result<impl IntoResponse, ClaimSeatError> {
+    let player = GamePlayer::try_from(player.as_str())
+        .map_err(|_| ClaimSeatError::InvalidSeat(player.clone()))?;
+    let mut conn = state.database().begin().await?;
+    if !Game::exists(&mut conn, game_id).await? {
+        return Err(ClaimSeatError::NotFound);
+    }
+
+    let token = seat_token(&jar, game_id);
+    let user_id = user.map(|CurrentUser(user)| user.id());
+    let token = Game::claim_seat(&mut conn, game_id, player, token, user_id).await?;
+
+    let api_game_board = ApiGameBoard::from(GameBoard::latest(&mut conn, game_id).await?);
+    conn.commit().await?;
+
+    // Let everyone else know the seat is taken
+    tx.send(
+        game_id,
+        GameBoardTemplate {
+            api_game_board,
+            viewer: ApiViewer::default(),
+        },
+    );
+
+    // Reload the page so the new player's board and stream pick up their seat
+    Ok((
+        jar.add(seat_cookie(game_id, token)),
+        [("HX-Refresh", "true")],
+        StatusCode::OK,
+    ))
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClaimSeatError {
+    #[error("sqlx error: {0}")]
+    Sqlx(#[from] sqlx::Error),
+    #[error("game error: {0}")]
+    Game(#[from] GameError),
+    #[error("invalid seat: {0}")]
+    InvalidSeat(String),
+    #[error("game not found")]
+    NotFound,
+}
+
+impl IntoResponse for ClaimSeatError {
+    fn into_response(self) -> Response {
+        match self {
+            ClaimSeatError::NotFound => {
+                let body = format!("{}", self);
+                (axum::http::StatusCode::NOT_FOUND, body).into_response()
+            }
+            ClaimSeatError::InvalidSeat(_) => {
+                let body = format!("{}", self);
+                (axum::http::StatusCode::BAD_REQUEST, body).into_response()
+            }
+            ClaimSeatError::Game(GameError::SeatTaken(_)) => {
+                let body = format!("{}", self);
+                (axum::http::StatusCode::CONFLICT, body).into_response()
+            }
+            _ => {
+                let body = format!("{}", self);
+                (axum::http::StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
+            }
+        }
+    }
+}
@@ -1,20 +1,127 @@
 use askama::Template;
 use axum::{
-    extract::State,
+    extract::{rejection::FormRejection, State},
+    http::{header::CONTENT_TYPE, HeaderMap},
     response::{IntoResponse, Response},
+    Extension, Form,
 };
+use axum_extra::extract::cookie::CookieJar;
+use sqlx::types::Uuid;
 
+use crate::api::bots;
 use crate::api::models::ApiGameItem;
-use crate::database::models::NewGame;
+use crate::api::negotiate::Format;
+use crate::api::seats::seat_cookie;
+use crate::api::session::CurrentUser;
+use crate::database::models::{Game, GameBot, GameError, GamePlayer, NewGame, TimeControl};
 use crate::AppState;
 
-pub async fn handler(State(state): State<AppState>) -> Result<impl IntoResponse, CreateGameError> {
-    let game = NewGame::create(&state.database()).await?;
+use super::watch_game_sse::GameUpdateStream;
 
+/// Games are between two people unless a bot is picked, and untimed unless a time control
+///  like `5+3` or `3d` is picked. The creator is seated on the side they pick, which is
+///  white by default against a bot.
+#[derive(serde::Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct CreateGameRequest {
+    bot: Option<String>,
+    depth: Option<i16>,
+    player: Option<String>,
+    time_control: Option<String>,
+}
+
+pub async fn handler(
+    State(state): State<AppState>,
+    Extension(tx): Extension<GameUpdateStream>,
+    user: Option<CurrentUser>,
+    jar: CookieJar,
+    format: Format,
+    headers: HeaderMap,
+    request: Result<Form<CreateGameRequest>, FormRejection>,
+) -> Result<impl IntoResponse, CreateGameError> {
+    // A bare POST, without a form, is a game between two people
+    let request = match request {
+        Ok(Form(request)) => request,
+        Err(FormRejection::InvalidFormContentType(_)) if !headers.contains_key(CONTENT_TYPE) => {
+            CreateGameRequest::default()
+        }
+        Err(rejection) => return Err(CreateGameError::InvalidForm(rejection.body_text())),
+    };
+    let user_id = user.map(|CurrentUser(user)| user.id());
+    let (game, token) = create(state, tx, user_id, &request).await?;
+
+    let jar = match token {
+        Some(token) => jar.add(seat_cookie(game.id(), token)),
+        None => jar,
+    };
     let api_game = ApiGameItem::from(game);
-    Ok(GameItemTemplate {
-        game_item: api_game,
-    })
+    Ok((
+        jar,
+        format.respond(api_game, |game_item| GameItemTemplate { game_item }),
+    ))
+}
+
+/// Create a game, seating its creator if they picked a side or are playing a bot. Returns
+///  the creator's seat token along with the game if they were seated.
+pub async fn create(
+    state: AppState,
+    tx: GameUpdateStream,
+    user_id: Option<Uuid>,
+    request: &CreateGameRequest,
+) -> Result<(Game, Option<Uuid>), CreateGameError> {
+    let bot = match request.bot.as_deref() {
+        None | Some("") => None,
+        Some(bot) => {
+            Some(GameBot::try_from(bot).map_err(|_| CreateGameError::InvalidBot(bot.to_string()))?)
+        }
+    };
+
+    let time_control = match request.time_control.as_deref() {
+        None | Some("") => None,
+        Some(time_control) => Some(
+            TimeControl::try_from(time_control)
+                .map_err(|_| CreateGameError::InvalidTimeControl(time_control.to_string()))?,
+        ),
+    };
+
+    let player = match request.player.as_deref() {
+        None | Some("") => None,
+        Some(player) => Some(
+            GamePlayer::try_from(player)
+                .map_err(|_| CreateGameError::InvalidSeat(player.to_string()))?,
+        ),
+    };
+
+    match bot {
+        None => {
+            let mut conn = state.database().begin().await?;
+            let game = NewGame::create(&mut conn, time_control).await?;
+            let token = match player {
+                Some(player) => {
+                    Some(Game::claim_seat(&mut conn, game.id(), player, None, user_id).await?)
+                }
+                None => None,
+            };
+            conn.commit().await?;
+            Ok((game, token))
+        }
+        Some(bot) => {
+            let player = player.unwrap_or(GamePlayer::White);
+            let depth = request.depth.unwrap_or(2);
+
+            let mut conn = state.database().begin().await?;
+            let game =
+                NewGame::create_against_bot(&mut conn, bot, depth, player.opponent(), time_control)
+                    .await?;
+            let token = Game::claim_seat(&mut conn, game.id(), player, None, user_id).await?;
+            conn.commit().await?;
+
+            // The bot opens the game if it's playing white
+            bots::spawn_reply(state.clone(), tx, game.id());
+
+            Ok((game, Some(token)))
+        }
+    }
 }
 
 #[derive(Template)]
@@ -27,11 +134,33 @@ struct GameItemTemplate {
 pub enum CreateGameError {
     #[error("sqlx error: {0}")]
     Sqlx(#[from] sqlx::Error),
+    #[error("game error: {0}")]
+    Game(#[from] GameError),
+    #[error("invalid form: {0}")]
+    InvalidForm(String),
+    #[error("invalid bot: {0}")]
+    InvalidBot(String),
+    #[error("invalid seat: {0}")]
+    InvalidSeat(String),
+    #[error("invalid time control: {0}")]
+    InvalidTimeControl(String),
 }
 
 impl IntoResponse for CreateGameError {
     fn into_response(self) -> Response {
-        let body = format!("{}", self);
-        (axum::http::StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
+        match self {
+            CreateGameError::InvalidForm(_)
+            | CreateGameError::InvalidBot(_)
+            | CreateGameError::InvalidSeat(_)
+            | CreateGameError::InvalidTimeControl(_)
+            | CreateGameError::Game(GameError::InvalidBotDepth(_)) => {
+                let body = format!("{}", self);
+                (axum::http::StatusCode::BAD_REQUEST, body).into_response()
+            }
+            _ => {
+                let body = format!("{}", self);
+                (axum::http::StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
+            }
+        }
     }
 }
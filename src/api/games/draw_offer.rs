@@ -0,0 +1,108 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Form,
+};
+use axum_extra::extract::cookie::CookieJar;
+use sqlx::types::Uuid;
+
+use crate::api::models::{ApiGameBoard, ApiViewer};
+use crate::api::seats::seat_token;
+use crate::api::templates::GameBoardTemplate;
+use crate::database::models::{Game, GameBoard, GameError};
+use crate::AppState;
+
+use super::watch_game_sse::GameUpdateStream;
+
+#[derive(serde::Deserialize, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum DrawOfferAction {
+    Offer,
+    Accept,
+    Decline,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub struct DrawOfferRequest {
+    action: DrawOfferAction,
+}
+
+pub async fn handler(
+    State(state): State<AppState>,
+    Extension(tx): Extension<GameUpdateStream>,
+    Path(game_id): Path<Uuid>,
+    jar: CookieJar,
+    Form(request): Form<DrawOfferRequest>,
+) -> Result<impl IntoResponse, DrawOfferError> {
+    let mut conn = state.database().begin().await?;
+    if !Game::exists(&mut conn, game_id).await? {
+        return Err(DrawOfferError::NotFound);
+    }
+
+    let token = seat_token(&jar, game_id);
+    match request.action {
+        DrawOfferAction::Offer => GameBoard::offer_draw(&mut conn, game_id, token).await?,
+        DrawOfferAction::Accept => {
+            GameBoard::respond_to_draw(&mut conn, game_id, token, true).await?
+        }
+        DrawOfferAction::Decline => {
+            GameBoard::respond_to_draw(&mut conn, game_id, token, false).await?
+        }
+    }
+
+    let api_game_board = ApiGameBoard::from(GameBoard::latest(&mut conn, game_id).await?);
+    conn.commit().await?;
+
+    tx.send(
+        game_id,
+        GameBoardTemplate {
+            api_game_board,
+            viewer: ApiViewer::default(),
+        },
+    );
+
+    Ok(StatusCode::OK)
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DrawOfferError {
+    #[error("sqlx error: {0}")]
+    Sqlx(#[from] sqlx::Error),
+    #[error("game error: {0}")]
+    Game(#[from] GameError),
+    #[error("game not found")]
+    NotFound,
+}
+
+impl IntoResponse for DrawOfferError {
+    fn into_response(self) -> Response {
+        match self {
+            DrawOfferError::NotFound => {
+                let body = format!("{}", self);
+                (axum::http::StatusCode::NOT_FOUND, body).into_response()
+            }
+            DrawOfferError::Game(e) => match e {
+                GameError::GameComplete
+                | GameError::GameAbandoned
+                | GameError::DrawAlreadyOffered
+                | GameError::NoDrawOffer => {
+                    let body = format!("{}", e);
+                    (axum::http::StatusCode::BAD_REQUEST, body).into_response()
+                }
+                GameError::WrongSeat(_) => {
+                    let body = format!("{}", e);
+                    (axum::http::StatusCode::FORBIDDEN, body).into_response()
+                }
+                _ => {
+                    let body = format!("internal server error: {}", e);
+                    (axum::http::StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
+                }
+            },
+            _ => {
+                let body = format!("{}", self);
+                (axum::http::StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
+            }
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use askama::Template;
+use axum::{
+    extract::{multipart::MultipartError, Multipart, State},
+    response::{IntoResponse, Response},
+};
+
+use crate::api::models::ApiGameItem;
+use crate::database::models::{GameError, NewGame, PgnError, PgnGame};
+use crate::AppState;
+
+/// Create a game for every game in the uploaded PGN. Every `pgn` field counts, whether
+///  it's a file or pasted text. Either every game is imported or, if one fails, none are.
+pub async fn handler(
+    State(state): State<AppState>,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse, ImportGamesError> {
+    let mut pgn = String::new();
+    while let Some(field) = multipart.next_field().await? {
+        if field.name() == Some("pgn") {
+            pgn.push_str(&field.text().await?);
+            pgn.push('\n');
+        }
+    }
+    let pgn_games = PgnGame::parse_all(&pgn)?;
+
+    let mut conn = state.database().begin().await?;
+    let mut game_items = Vec::with_capacity(pgn_games.len());
+    for (index, pgn_game) in pgn_games.iter().enumerate() {
+        let game = NewGame::import(&mut conn, index + 1, pgn_game).await?;
+        game_items.push(ApiGameItem::from(game));
+    }
+    conn.commit().await?;
+
+    Ok(GameItemsTemplate { game_items })
+}
+
+#[derive(Template)]
+#[template(path = "game_items.html")]
+struct GameItemsTemplate {
+    game_items: Vec<ApiGameItem>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ImportGamesError {
+    #[error("sqlx error: {0}")]
+    Sqlx(#[from] sqlx::Error),
+    #[error("game error: {0}")]
+    Game(#[from] GameError),
+    #[error("invalid pgn: {0}")]
+    Pgn(#[from] PgnError),
+    #[error("invalid upload: {0}")]
+    Upload(#[from] MultipartError),
+}
+
+impl IntoResponse for ImportGamesError {
+    fn into_response(self) -> Response {
+        match self {
+            ImportGamesError::Pgn(_)
+            | ImportGamesError::Upload(_)
+            | ImportGamesError::Game(GameError::IllegalPgnMove(_, _, _))
+            | ImportGamesError::Game(GameError::UnsupportedPgnStart(_)) => {
+                let body = format!("{}", self);
+                (axum::http::StatusCode::BAD_REQUEST, body).into_response()
+            }
+            _ => {
+                let body = format!("{}", self);
+                (axum::http::StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
+            }
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use axum::{
+    extract::{Path, State},
+    response::{IntoResponse, Redirect, Response},
+};
+use axum_extra::extract::cookie::CookieJar;
+use sqlx::types::Uuid;
+
+use crate::api::seats::seat_cookie;
+use crate::database::models::{Game, GameError};
+use crate::AppState;
+
+/// Follow a join link to take a seat token to another browser
+pub async fn handler(
+    State(state): State<AppState>,
+    Path((game_id, token)): Path<(Uuid, Uuid)>,
+    jar: CookieJar,
+) -> Result<impl IntoResponse, JoinSeatError> {
+    let mut conn = state.database().acquire().await?;
+    if !Game::exists(&mut conn, game_id).await? {
+        return Err(JoinSeatError::NotFound);
+    }
+    if Game::seats(&mut conn, game_id, token).await?.is_empty() {
+        return Err(JoinSeatError::InvalidToken);
+    }
+
+    Ok((
+        jar.add(seat_cookie(game_id, token)),
+        Redirect::to(&format!("/games/{}", game_id)),
+    ))
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum JoinSeatError {
+    #[error("sqlx error: {0}")]
+    Sqlx(#[from] sqlx::Error),
+    #[error("game error: {0}")]
+    Game(#[from] GameError),
+    #[error("join link does not hold a seat in this game")]
+    InvalidToken,
+    #[error("game not found")]
+    NotFound,
+}
+
+impl IntoResponse for JoinSeatError {
+    fn into_response(self) -> Response {
+        match self {
+            JoinSeatError::NotFound | JoinSeatError::InvalidToken => {
+                let body = format!("{}", self);
+                (axum::http::StatusCode::NOT_FOUND, body).into_response()
+            }
+            _ => {
+                let body = format!("{}", self);
+                (axum::http::StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
+            }
+        }
+    }
+}
@@ -1,50 +1,181 @@
 use axum::{
     extract::{Path, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::{IntoResponse, Response},
     Extension, Form,
 };
+use axum_extra::extract::cookie::CookieJar;
 use sqlx::types::Uuid;
 
-use crate::api::models::ApiGameBoard;
+use crate::api::bots;
+use crate::api::models::{ApiGameBoard, ApiGameState, ApiViewer};
+use crate::api::negotiate::Format;
+use crate::api::seats::seat_token;
 use crate::api::templates::GameBoardTemplate;
-use crate::database::models::{Game, GameBoard, GameError};
+use crate::api::v1::ApiError;
+use crate::database::models::{
+    Game, GameBoard, GameError, MoveRequest, MAX_IDEMPOTENCY_KEY_LENGTH,
+};
 use crate::AppState;
 
 use super::watch_game_sse::GameUpdateStream;
 
+/// Header clients can send an idempotency key in, instead of the `idempotencyKey` field
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// `ply` is the ply the board was at when the move was picked. If it's given and the game
+///  has moved on since, the move is refused with the current state of the game.
 #[derive(serde::Deserialize, Debug)]
 pub struct MakeMoveRequest {
     #[serde(rename = "uciMove")]
     uci_move: String,
     resign: Option<bool>,
+    ply: Option<u16>,
+    #[serde(rename = "idempotencyKey")]
+    idempotency_key: Option<String>,
 }
 
 pub async fn handler(
     State(state): State<AppState>,
     Extension(tx): Extension<GameUpdateStream>,
     Path(game_id): Path<Uuid>,
+    jar: CookieJar,
+    headers: HeaderMap,
+    format: Format,
     Form(request): Form<MakeMoveRequest>,
-) -> Result<impl IntoResponse, ReadBoardError> {
-    let uci_move = request.uci_move;
-    let resign = request.resign.unwrap_or(false);
+) -> Result<Response, ReadBoardError> {
+    let token = seat_token(&jar, game_id);
+    let idempotency_key = request
+        .idempotency_key
+        .or_else(|| idempotency_key(&headers))
+        .filter(|key| !key.is_empty());
+    let attempt = MoveAttempt {
+        uci_move: &request.uci_move,
+        resign: request.resign.unwrap_or(false),
+        ply: request.ply,
+        idempotency_key: idempotency_key.as_deref(),
+    };
+    match make_move(state, tx, game_id, token, attempt).await {
+        // The browser picks up the new board from the game's event stream
+        Ok(api_game_board) => {
+            let response = format.respond(ApiGameState::from(api_game_board), |_| StatusCode::OK);
+            Ok(response.into_response())
+        }
+        Err(ReadBoardError::Conflict(e, game_state)) => {
+            let response = format.respond(*game_state, |_| e.to_string());
+            Ok((StatusCode::CONFLICT, response).into_response())
+        }
+        // Clients asking for JSON get their errors, and why a move was rejected, as JSON too
+        Err(e) if format == Format::Json => Ok(ApiError::from(e).into_response()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Read the idempotency key a request was sent with, if it has one
+pub fn idempotency_key(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|key| key.to_str().ok())
+        .map(|key| key.to_string())
+}
+
+/// A move (or resignation) a client asked for
+pub struct MoveAttempt<'a> {
+    pub uci_move: &'a str,
+    pub resign: bool,
+    /// The ply the client was moving from, if it said
+    pub ply: Option<u16>,
+    /// Attempts sent again with the same key get the first one's outcome, and aren't
+    ///  played twice
+    pub idempotency_key: Option<&'a str>,
+}
+
+/// Play a move (or resign) for the seat holding the token, let everyone watching know, and
+///  return the board as it stands afterwards
+pub async fn make_move(
+    state: AppState,
+    tx: GameUpdateStream,
+    game_id: Uuid,
+    token: Option<Uuid>,
+    attempt: MoveAttempt<'_>,
+) -> Result<ApiGameBoard, ReadBoardError> {
+    let (uci_move, resign) = (attempt.uci_move, attempt.resign);
+    if attempt
+        .idempotency_key
+        .is_some_and(|key| key.len() > MAX_IDEMPOTENCY_KEY_LENGTH)
+    {
+        return Err(ReadBoardError::InvalidIdempotencyKey);
+    }
+
     let mut conn = state.database().begin().await?;
     if !Game::exists(&mut conn, game_id).await? {
         return Err(ReadBoardError::NotFound);
     }
 
+    // Answer a repeat with the board the original left behind, without playing it again.
+    //  The game stays locked until we're done, so a repeat racing the original waits for it.
+    //  Only requests that went through are remembered -- the rest changed nothing, so
+    //  they're safe to try again.
+    if let Some(key) = attempt.idempotency_key {
+        GameBoard::lock(&mut conn, game_id).await?;
+        if let Some(original) = MoveRequest::find(&mut conn, game_id, key).await? {
+            if !original.matches(uci_move, resign) {
+                return Err(ReadBoardError::IdempotencyKeyReused(key.to_string()));
+            }
+            let game_board = match GameBoard::at(&mut conn, game_id, original.ply()).await? {
+                Some(game_board) => game_board,
+                None => GameBoard::latest(&mut conn, game_id).await?,
+            };
+            return Ok(ApiGameBoard::from(game_board));
+        }
+    }
+
     // Returns the updated board if the move was valid. Otherwise, returns the latest board.
-    GameBoard::make_move(&mut conn, game_id, &uci_move, resign).await?;
+    match GameBoard::make_move(&mut conn, game_id, token, attempt.ply, uci_move, resign).await {
+        Ok(()) => {}
+        // Hand back the board the client missed, so it can pick its move again
+        Err(e @ GameError::StaleMove(_, _)) => {
+            let game_board = GameBoard::latest(&mut conn, game_id).await?;
+            let game_state = ApiGameState::from(ApiGameBoard::from(game_board));
+            return Err(ReadBoardError::Conflict(e, Box::new(game_state)));
+        }
+        // The mover lost on time instead. That stands, so keep it and let everyone know.
+        Err(e @ GameError::TimedOut(_)) => {
+            let api_game_board = ApiGameBoard::from(GameBoard::latest(&mut conn, game_id).await?);
+            conn.commit().await?;
+            tx.send(
+                game_id,
+                GameBoardTemplate {
+                    api_game_board: api_game_board.clone(),
+                    viewer: ApiViewer::default(),
+                },
+            );
+            let game_state = ApiGameState::from(api_game_board);
+            return Err(ReadBoardError::Conflict(e, Box::new(game_state)));
+        }
+        Err(e) => return Err(e.into()),
+    }
 
     // Wow this really sucks, the client should just read this again
     let api_game_board = ApiGameBoard::from(GameBoard::latest(&mut conn, game_id).await?);
+    if let Some(key) = attempt.idempotency_key {
+        let ply = api_game_board.ply() as i32;
+        MoveRequest::record(&mut conn, game_id, key, uci_move, resign, ply).await?;
+    }
     conn.commit().await?;
 
-    if tx.send(GameBoardTemplate { api_game_board }).is_err() {
-        tracing::warn!("failed to send game update: game_id={}", game_id);
-    }
+    tx.send(
+        game_id,
+        GameBoardTemplate {
+            api_game_board: api_game_board.clone(),
+            viewer: ApiViewer::default(),
+        },
+    );
 
-    Ok(StatusCode::OK)
+    // Let the computer reply if this game is against a bot
+    bots::spawn_reply(state, tx, game_id);
+
+    Ok(api_game_board)
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -55,6 +186,15 @@ pub enum ReadBoardError {
     Game(#[from] GameError),
     #[error("game not found")]
     NotFound,
+    #[error("{0}")]
+    Conflict(GameError, Box<ApiGameState>),
+    #[error(
+        "idempotency keys can be at most {} characters",
+        MAX_IDEMPOTENCY_KEY_LENGTH
+    )]
+    InvalidIdempotencyKey,
+    #[error("idempotency key {0} was already used for a different move")]
+    IdempotencyKeyReused(String),
 }
 
 impl IntoResponse for ReadBoardError {
@@ -64,11 +204,29 @@ impl IntoResponse for ReadBoardError {
                 let body = format!("{}", self);
                 (axum::http::StatusCode::NOT_FOUND, body).into_response()
             }
+            ReadBoardError::Conflict(_, _) => {
+                let body = format!("{}", self);
+                (axum::http::StatusCode::CONFLICT, body).into_response()
+            }
+            ReadBoardError::InvalidIdempotencyKey => {
+                let body = format!("{}", self);
+                (axum::http::StatusCode::BAD_REQUEST, body).into_response()
+            }
+            ReadBoardError::IdempotencyKeyReused(_) => {
+                let body = format!("{}", self);
+                (axum::http::StatusCode::UNPROCESSABLE_ENTITY, body).into_response()
+            }
             ReadBoardError::Game(e) => match e {
-                GameError::InvalidMove(_) | GameError::GameComplete => {
+                GameError::InvalidMove(_, _)
+                | GameError::GameComplete
+                | GameError::GameAbandoned => {
                     let body = format!("{}", e);
                     (axum::http::StatusCode::BAD_REQUEST, body).into_response()
                 }
+                GameError::WrongSeat(_) => {
+                    let body = format!("{}", e);
+                    (axum::http::StatusCode::FORBIDDEN, body).into_response()
+                }
                 _ => {
                     let body = format!("internal server error: {}", e);
                     (axum::http::StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
@@ -81,3 +239,64 @@ impl IntoResponse for ReadBoardError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use futures::future::join_all;
+    use sqlx::PgPool;
+
+    use super::*;
+
+    use crate::database::models::{GamePlayer, NewGame};
+
+    const RACERS: usize = 32;
+
+    #[sqlx::test]
+    async fn racing_moves_conflict_instead_of_failing(pool: PgPool) {
+        let state = AppState::new(pool.clone());
+        let tx = GameUpdateStream::new();
+        let mut conn = pool.acquire().await.unwrap();
+        let game = NewGame::create(&mut conn, None).await.unwrap();
+        let game_id = game.id();
+        let white = Game::claim_seat(&mut conn, game_id, GamePlayer::White, None, None)
+            .await
+            .unwrap();
+
+        // Every racer moves white from the starting position. Half say so, half don't.
+        let racers = (0..RACERS).map(|i| {
+            let (state, tx) = (state.clone(), tx.clone());
+            let ply = (i % 2 == 1).then_some(0);
+            tokio::spawn(async move {
+                let attempt = MoveAttempt {
+                    uci_move: "e2e4",
+                    resign: false,
+                    ply,
+                    idempotency_key: None,
+                };
+                make_move(state, tx, game_id, Some(white), attempt).await
+            })
+        });
+        let results = join_all(racers)
+            .await
+            .into_iter()
+            .map(|result| result.unwrap())
+            .collect::<Vec<_>>();
+
+        let played = results.iter().filter(|result| result.is_ok()).count();
+        assert_eq!(played, 1);
+        for result in results {
+            match result {
+                Ok(_) => {}
+                Err(ReadBoardError::Conflict(GameError::StaleMove(0, 1), game_state)) => {
+                    assert_eq!(game_state.game.moves, vec![Some("e4".to_string())]);
+                }
+                // Racers that didn't say which ply they were moving from find it's black's turn
+                Err(ReadBoardError::Game(GameError::WrongSeat(GamePlayer::Black))) => {}
+                Err(e) => panic!("unexpected error: {}", e),
+            }
+        }
+
+        let game_board = GameBoard::latest(&mut conn, game_id).await.unwrap();
+        assert_eq!(game_board.moves(), [Some("e4".to_string())]);
+    }
+}
@@ -1,5 +1,12 @@
+pub mod claim_seat;
 pub mod create_game;
+pub mod draw_offer;
+pub mod import_games;
+pub mod join_seat;
 pub mod make_move;
 pub mod read_all_games;
 pub mod read_game;
+pub mod read_game_pgn;
+pub mod read_position;
+pub mod takeback;
 pub mod watch_game_sse;
@@ -1,26 +1,129 @@
 use askama::Template;
 use axum::{
-    extract::State,
+    extract::{Query, State},
     response::{IntoResponse, Response},
 };
 
-use crate::api::models::ApiGameItem;
-use crate::database::models::{Game, GameError};
+use crate::api::models::{ApiGameItem, ApiGameList};
+use crate::api::negotiate::Format;
+use crate::database::models::{
+    Game, GameCursor, GameDirection, GameError, GameListQuery, GameOrder, DEFAULT_PAGE_SIZE,
+    MAX_PAGE_SIZE, MIN_PAGE_SIZE,
+};
 use crate::AppState;
 
+/// Filters are left out, or blank, to match every game
+#[derive(serde::Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct ReadAllGamesRequest {
+    sort: GameOrder,
+    order: GameDirection,
+    status: Option<String>,
+    winner: Option<String>,
+    outcome: Option<String>,
+    cursor: Option<String>,
+    limit: Option<i64>,
+}
+
+impl ReadAllGamesRequest {
+    /// Check the request's filters and cursor, and clamp its page size
+    pub fn query(&self) -> Result<GameListQuery, ReadAllGamesError> {
+        Ok(GameListQuery {
+            order: self.sort,
+            direction: self.order,
+            status: parse_filter("status", &self.status)?,
+            winner: parse_filter("winner", &self.winner)?,
+            outcome: parse_filter("outcome", &self.outcome)?,
+            after: parse_filter("cursor", &self.cursor)?,
+            limit: self
+                .limit
+                .unwrap_or(DEFAULT_PAGE_SIZE)
+                .clamp(MIN_PAGE_SIZE, MAX_PAGE_SIZE),
+        })
+    }
+}
+
 pub async fn handler(
     State(state): State<AppState>,
+    Query(request): Query<ReadAllGamesRequest>,
+    format: Format,
 ) -> Result<impl IntoResponse, ReadAllGamesError> {
+    let query = request.query()?;
+
     let mut conn = state.database().acquire().await?;
-    let games = Game::read_all(&mut conn).await?;
-    let game_items = games.into_iter().map(ApiGameItem::from).collect();
-    Ok(GameList { game_items })
+    let (games, next) = Game::read_page(&mut conn, &query).await?;
+    let next_page = next.map(|next| next_page(&request, &query, next));
+
+    Ok(
+        format.respond(ApiGameList::new(games, next), move |game_list| {
+            let game_items = game_list.games;
+            // Later pages only add rows to the list that's already there
+            if query.after.is_some() {
+                GamePage {
+                    game_items,
+                    next_page,
+                }
+                .into_response()
+            } else {
+                GameList {
+                    game_items,
+                    next_page,
+                    sort: query.order.to_string(),
+                    order: query.direction.to_string(),
+                    status: request.status.unwrap_or_default(),
+                    winner: request.winner.unwrap_or_default(),
+                    outcome: request.outcome.unwrap_or_default(),
+                }
+                .into_response()
+            }
+        }),
+    )
+}
+
+/// Read an optional filter, treating a blank value as no filter
+fn parse_filter<'a, T: TryFrom<&'a str>>(
+    name: &'static str,
+    value: &'a Option<String>,
+) -> Result<Option<T>, ReadAllGamesError> {
+    match value.as_deref() {
+        None | Some("") => Ok(None),
+        Some(value) => T::try_from(value)
+            .map(Some)
+            .map_err(|_| ReadAllGamesError::InvalidFilter(name, value.to_string())),
+    }
+}
+
+/// The link to the page after this one, with the same filters and order
+fn next_page(request: &ReadAllGamesRequest, query: &GameListQuery, next: GameCursor) -> String {
+    format!(
+        "/games?sort={}&order={}&status={}&winner={}&outcome={}&limit={}&cursor={}",
+        query.order,
+        query.direction,
+        request.status.as_deref().unwrap_or_default(),
+        request.winner.as_deref().unwrap_or_default(),
+        request.outcome.as_deref().unwrap_or_default(),
+        query.limit,
+        next,
+    )
 }
 
 #[derive(Template)]
 #[template(path = "game_list.html")]
 struct GameList {
     game_items: Vec<ApiGameItem>,
+    next_page: Option<String>,
+    sort: String,
+    order: String,
+    status: String,
+    winner: String,
+    outcome: String,
+}
+
+#[derive(Template)]
+#[template(path = "game_page.html")]
+struct GamePage {
+    game_items: Vec<ApiGameItem>,
+    next_page: Option<String>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -29,11 +132,21 @@ pub enum ReadAllGamesError {
     Sqlx(#[from] sqlx::Error),
     #[error("game error: {0}")]
     Game(#[from] GameError),
+    #[error("invalid {0}: {1}")]
+    InvalidFilter(&'static str, String),
 }
 
 impl IntoResponse for ReadAllGamesError {
     fn into_response(self) -> Response {
-        let body = format!("{}", self);
-        (axum::http::StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
+        match self {
+            ReadAllGamesError::InvalidFilter(_, _) => {
+                let body = format!("{}", self);
+                (axum::http::StatusCode::BAD_REQUEST, body).into_response()
+            }
+            _ => {
+                let body = format!("{}", self);
+                (axum::http::StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
+            }
+        }
     }
 }
@@ -2,9 +2,12 @@ use axum::{
     extract::{Path, State},
     response::{IntoResponse, Response},
 };
+use axum_extra::extract::cookie::CookieJar;
 use sqlx::types::Uuid;
 
-use crate::api::models::ApiGameBoard;
+use crate::api::models::{ApiGameBoard, ApiGameState};
+use crate::api::negotiate::Format;
+use crate::api::seats::viewer;
 use crate::api::templates::GameIndexTemplate;
 use crate::database::models::{Game, GameBoard, GameError};
 use crate::AppState;
@@ -12,6 +15,8 @@ use crate::AppState;
 pub async fn handler(
     State(state): State<AppState>,
     Path(game_id): Path<Uuid>,
+    jar: CookieJar,
+    format: Format,
 ) -> Result<impl IntoResponse, ReadBoardError> {
     let mut conn = state.database().acquire().await?;
     if !Game::exists(&mut conn, game_id).await? {
@@ -21,8 +26,16 @@ pub async fn handler(
     let game_board = GameBoard::latest(&mut conn, game_id).await?;
 
     let api_game_board = ApiGameBoard::from(game_board);
-  
-    Ok(GameIndexTemplate { api_game_board })
+    let viewer = viewer(&mut conn, &jar, game_id).await?;
+
+    Ok(
+        format.respond(ApiGameState::from(api_game_board), |game_state| {
+            GameIndexTemplate {
+                api_game_board: game_state.game,
+                viewer,
+            }
+        }),
+    )
 }
 
 #[derive(Debug, thiserror::Error)]
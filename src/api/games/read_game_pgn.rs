@@ -0,0 +1,54 @@
+use axum::{
+    extract::{Path, State},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use sqlx::types::Uuid;
+
+use crate::api::models::ApiGamePgn;
+use crate::database::models::{Game, GameBoard, GameError};
+use crate::AppState;
+
+pub async fn handler(
+    State(state): State<AppState>,
+    Path(game_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ReadGamePgnError> {
+    let mut conn = state.database().acquire().await?;
+    if !Game::exists(&mut conn, game_id).await? {
+        return Err(ReadGamePgnError::NotFound);
+    }
+
+    let game = Game::read(&mut conn, game_id).await?;
+    let latest = GameBoard::latest(&mut conn, game_id).await?;
+    let pgn = ApiGamePgn::new(&game, &latest)?;
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/x-chess-pgn")],
+        pgn.to_string(),
+    ))
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReadGamePgnError {
+    #[error("sqlx error: {0}")]
+    Sqlx(#[from] sqlx::Error),
+    #[error("game error: {0}")]
+    Game(#[from] GameError),
+    #[error("game not found")]
+    NotFound,
+}
+
+impl IntoResponse for ReadGamePgnError {
+    fn into_response(self) -> Response {
+        match self {
+            ReadGamePgnError::NotFound => {
+                let body = format!("{}", self);
+                (axum::http::StatusCode::NOT_FOUND, body).into_response()
+            }
+            _ => {
+                let body = format!("{}", self);
+                (axum::http::StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
+            }
+        }
+    }
+}
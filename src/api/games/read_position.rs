@@ -0,0 +1,61 @@
+use axum::{
+    extract::{Path, State},
+    response::{IntoResponse, Response},
+};
+use sqlx::types::Uuid;
+
+use crate::api::models::ApiGameBoard;
+use crate::api::templates::GamePositionTemplate;
+use crate::database::models::{Game, GameBoard, GameError};
+use crate::AppState;
+
+pub async fn handler(
+    State(state): State<AppState>,
+    Path((game_id, ply)): Path<(Uuid, i32)>,
+) -> Result<impl IntoResponse, ReadPositionError> {
+    let mut conn = state.database().acquire().await?;
+    if !Game::exists(&mut conn, game_id).await? {
+        return Err(ReadPositionError::NotFound);
+    }
+
+    let last_ply = GameBoard::latest(&mut conn, game_id)
+        .await?
+        .board()
+        .moves_played() as i32;
+    let game_board = GameBoard::at(&mut conn, game_id, ply)
+        .await?
+        .ok_or(ReadPositionError::PositionNotFound(ply))?;
+
+    Ok(GamePositionTemplate {
+        api_game_board: ApiGameBoard::from(game_board),
+        ply,
+        last_ply,
+    })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReadPositionError {
+    #[error("sqlx error: {0}")]
+    Sqlx(#[from] sqlx::Error),
+    #[error("game error: {0}")]
+    Game(#[from] GameError),
+    #[error("game not found")]
+    NotFound,
+    #[error("position not found: ply {0}")]
+    PositionNotFound(i32),
+}
+
+impl IntoResponse for ReadPositionError {
+    fn into_response(self) -> Response {
+        match self {
+            ReadPositionError::NotFound | ReadPositionError::PositionNotFound(_) => {
+                let body = format!("{}", self);
+                (axum::http::StatusCode::NOT_FOUND, body).into_response()
+            }
+            _ => {
+                let body = format!("{}", self);
+                (axum::http::StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
+            }
+        }
+    }
+}
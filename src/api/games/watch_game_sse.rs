@@ -1,37 +1,171 @@
+use std::collections::HashMap;
 use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
 use std::time::Duration;
 
 use askama::Template;
 use axum::{
-    extract::Path,
-    response::{sse::Event, Sse},
+    extract::{Path, State},
+    http::HeaderMap,
+    response::{sse::Event, IntoResponse, Response, Sse},
     Extension,
 };
+use axum_extra::extract::cookie::CookieJar;
 use sqlx::types::Uuid;
-use tokio::sync::broadcast::Sender;
+use tokio::sync::broadcast::{self, Sender};
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
 use tokio_stream::wrappers::BroadcastStream;
 use tokio_stream::{Stream, StreamExt as _};
 
+use crate::api::models::{ApiGameBoard, ApiViewer};
+use crate::api::seats::viewer;
 use crate::api::templates::GameBoardTemplate;
+use crate::database::models::{Game, GameBoard, GameError};
+use crate::AppState;
+
+/// How many updates a single game's channel buffers before slow watchers start lagging.
+///  Broadcast channels round their capacity up to a power of two, so keep it one.
+const GAME_UPDATE_CAPACITY: usize = 16;
+
+type GameChannels = Arc<Mutex<HashMap<Uuid, Sender<GameBoardTemplate>>>>;
+
+/// Registry of per-game update channels. A game's channel is created lazily when its first
+///  watcher subscribes and dropped once its last watcher disconnects, so watchers only ever
+///  see updates for the game they're watching.
+#[derive(Clone, Default)]
+pub struct GameUpdateStream {
+    channels: GameChannels,
+}
+
+impl GameUpdateStream {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribe to updates for a single game
+    pub fn subscribe(&self, game_id: Uuid) -> GameSubscription {
+        let mut channels = self.channels.lock().expect("game channels poisoned");
+        let rx = channels
+            .entry(game_id)
+            .or_insert_with(|| broadcast::channel(GAME_UPDATE_CAPACITY).0)
+            .subscribe();
+        GameSubscription {
+            game_id,
+            channels: self.channels.clone(),
+            stream: Some(BroadcastStream::new(rx)),
+        }
+    }
+
+    /// Send an update to everyone watching a game. Returns how many watchers received it.
+    pub fn send(&self, game_id: Uuid, update: GameBoardTemplate) -> usize {
+        let channels = self.channels.lock().expect("game channels poisoned");
+        let watchers = match channels.get(&game_id) {
+            Some(tx) => tx.send(update).unwrap_or(0),
+            None => 0,
+        };
+        if watchers == 0 {
+            tracing::debug!("no watchers for game update: game_id={}", game_id);
+        }
+        watchers
+    }
+}
+
+/// A stream of updates for a single game. Drops the game's channel from the registry
+///  when the last subscription goes away.
+pub struct GameSubscription {
+    game_id: Uuid,
+    channels: GameChannels,
+    stream: Option<BroadcastStream<GameBoardTemplate>>,
+}
+
+impl Stream for GameSubscription {
+    type Item = Result<GameBoardTemplate, BroadcastStreamRecvError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match self.stream.as_mut() {
+            Some(stream) => Pin::new(stream).poll_next(cx),
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+impl Drop for GameSubscription {
+    fn drop(&mut self) {
+        // Release our receiver before checking if anyone else is still watching
+        self.stream.take();
+        let mut channels = match self.channels.lock() {
+            Ok(channels) => channels,
+            Err(_) => return,
+        };
+        if channels
+            .get(&self.game_id)
+            .is_some_and(|tx| tx.receiver_count() == 0)
+        {
+            channels.remove(&self.game_id);
+        }
+    }
+}
 
 // TODO: generalize and use the read_game_board handler
-pub type GameUpdateStream = Sender<GameBoardTemplate>;
 pub async fn handler(
+    State(state): State<AppState>,
     Path(game_id): Path<Uuid>,
     Extension(tx): Extension<GameUpdateStream>,
-) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
-    let rx = tx.subscribe();
-
-    let stream = BroadcastStream::new(rx);
-
-    // Catch all updata events for this game
-    Sse::new(
-        stream
-            .map(move |tagb| {
-                let tagb = tagb.unwrap();
-                Event::default()
-                    .event(format!("game-update-{}", game_id))
-                    .data(tagb.render().unwrap())
+    headers: HeaderMap,
+    jar: CookieJar,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, WatchGameError> {
+    let mut conn = state.database().acquire().await?;
+    if !Game::exists(&mut conn, game_id).await? {
+        return Err(WatchGameError::NotFound);
+    }
+
+    // Subscribe before reading any history so no updates slip through in between
+    let subscription = tx.subscribe(game_id);
+
+    // Updates are shared by every watcher, but each renders them from their own seat
+    let viewer = viewer(&mut conn, &jar, game_id).await?;
+
+    // A reconnecting browser tells us the last ply it saw -- replay what it missed
+    let last_ply = headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<i32>().ok());
+    let missed = match last_ply {
+        Some(ply) => GameBoard::since(&mut conn, game_id, ply)
+            .await?
+            .into_iter()
+            .map(|game_board| GameBoardTemplate {
+                api_game_board: ApiGameBoard::from(game_board),
+                viewer: ApiViewer::default(),
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let live = game_boards(subscription, move || latest_board(state.clone(), game_id));
+    let boards = tokio_stream::iter(missed).chain(live);
+
+    // Catch all updata events for this game, identified by the ply they show
+    Ok(Sse::new(
+        boards
+            .map(move |tagb| GameBoardTemplate {
+                viewer: viewer.clone(),
+                ..tagb
+            })
+            .filter_map(move |tagb| match tagb.render() {
+                Ok(html) => Some(
+                    Event::default()
+                        .event(format!("game-update-{}", game_id))
+                        .id(tagb.api_game_board.ply().to_string())
+                        .data(html),
+                ),
+                Err(e) => {
+                    tracing::error!("failed to render game update: game_id={} | {}", game_id, e);
+                    None
+                }
             })
             .map(Ok),
     )
@@ -39,5 +173,116 @@ pub async fn handler(
         axum::response::sse::KeepAlive::new()
             .interval(Duration::from_secs(60))
             .text("keep-alive-text"),
-    )
+    ))
+}
+
+/// Turn a game subscription into the boards to send to a watcher. If the watcher falls
+///  behind and misses updates, it's sent a fresh snapshot of the game instead.
+fn game_boards<S, F, Fut>(subscription: S, snapshot: F) -> impl Stream<Item = GameBoardTemplate>
+where
+    S: Stream<Item = Result<GameBoardTemplate, BroadcastStreamRecvError>>,
+    F: Fn() -> Fut,
+    Fut: Future<Output = Option<GameBoardTemplate>>,
+{
+    subscription
+        .then(move |update| {
+            let refresh = update.is_err().then(&snapshot);
+            async move {
+                match update {
+                    Ok(tagb) => Some(tagb),
+                    Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+                        tracing::warn!(
+                            "game watcher lagged, sending snapshot: skipped={}",
+                            skipped
+                        );
+                        refresh?.await
+                    }
+                }
+            }
+        })
+        .filter_map(|tagb| tagb)
+}
+
+/// Read the latest board for a game, logging rather than failing the stream on errors
+async fn latest_board(state: AppState, game_id: Uuid) -> Option<GameBoardTemplate> {
+    let mut conn = match state.database().acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            tracing::error!("failed to acquire connection: game_id={} | {}", game_id, e);
+            return None;
+        }
+    };
+    match GameBoard::latest(&mut conn, game_id).await {
+        Ok(game_board) => Some(GameBoardTemplate {
+            api_game_board: ApiGameBoard::from(game_board),
+            viewer: ApiViewer::default(),
+        }),
+        Err(e) => {
+            tracing::error!("failed to read latest board: game_id={} | {}", game_id, e);
+            None
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WatchGameError {
+    #[error("sqlx error: {0}")]
+    Sqlx(#[from] sqlx::Error),
+    #[error("game error: {0}")]
+    Game(#[from] GameError),
+    #[error("game not found")]
+    NotFound,
+}
+
+impl IntoResponse for WatchGameError {
+    fn into_response(self) -> Response {
+        match self {
+            WatchGameError::NotFound => {
+                let body = format!("{}", self);
+                (axum::http::StatusCode::NOT_FOUND, body).into_response()
+            }
+            _ => {
+                let body = format!("{}", self);
+                (axum::http::StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tagb(game_id: &str) -> GameBoardTemplate {
+        GameBoardTemplate {
+            api_game_board: ApiGameBoard::starting(game_id),
+            viewer: ApiViewer::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn lagged_watcher_receives_snapshot() {
+        let game_id = Uuid::new_v4();
+        let tx = GameUpdateStream::new();
+        let subscription = tx.subscribe(game_id);
+
+        // Overflow the channel before the watcher reads anything
+        let updates = GAME_UPDATE_CAPACITY + 5;
+        for i in 0..updates {
+            tx.send(game_id, tagb(&format!("update-{}", i)));
+        }
+
+        let boards = game_boards(subscription, || async { Some(tagb("snapshot")) });
+        tokio::pin!(boards);
+
+        let first = boards.next().await.unwrap();
+        assert_eq!(first.api_game_board.game_id(), "snapshot");
+
+        // After the snapshot the watcher picks up with the updates still buffered
+        let next = boards.next().await.unwrap();
+        assert_eq!(
+            next.api_game_board.game_id(),
+            format!("update-{}", updates - GAME_UPDATE_CAPACITY)
+        );
+    }
 }
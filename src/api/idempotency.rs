@@ -0,0 +1,30 @@
+use std::time::Duration;
+
+use crate::database::models::MoveRequest;
+use crate::AppState;
+
+/// How often to clean up old idempotency keys
+const EXPIRE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Forget move requests' idempotency keys once they're older than `ttl` in the background.
+///  Repeats after that are treated as new requests.
+pub fn spawn_expirer(state: AppState, ttl: Duration) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(EXPIRE_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = expire(&state, ttl).await {
+                tracing::error!("failed to expire idempotency keys: {}", e);
+            }
+        }
+    });
+}
+
+async fn expire(state: &AppState, ttl: Duration) -> Result<(), sqlx::Error> {
+    let mut conn = state.database().acquire().await?;
+    let expired = MoveRequest::expire(&mut conn, ttl.as_secs() as i64).await?;
+    if expired > 0 {
+        tracing::info!("expired {} idempotency keys", expired);
+    }
+    Ok(())
+}
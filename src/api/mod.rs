@@ -1,3 +1,12 @@
+pub mod abandonment;
+pub mod bots;
+pub mod clocks;
 pub mod games;
+pub mod idempotency;
 pub mod models;
+pub mod negotiate;
+pub mod seats;
+pub mod session;
 pub mod templates;
+pub mod users;
+pub mod v1;
@@ -0,0 +1,40 @@
+use serde::Serialize;
+
+/// One side's clock, as it stood when the board was rendered
+#[derive(Clone, Debug, Serialize)]
+pub struct ApiClock {
+    remaining_ms: i64,
+    running: bool,
+}
+
+impl ApiClock {
+    pub fn new(remaining_ms: i64, running: bool) -> Self {
+        Self {
+            remaining_ms: remaining_ms.max(0),
+            running,
+        }
+    }
+
+    pub fn remaining_ms(&self) -> i64 {
+        self.remaining_ms
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// The remaining time, e.g. `4:59`, `1:02:03` or `2d 03:04:05`. Keep this in sync with
+    ///  `formatClock` in `clock.js`, which takes over counting down in the browser.
+    pub fn remaining(&self) -> String {
+        let total_seconds = self.remaining_ms / 1000;
+        let (days, hours) = (total_seconds / 86400, total_seconds / 3600 % 24);
+        let (minutes, seconds) = (total_seconds / 60 % 60, total_seconds % 60);
+        if days > 0 {
+            format!("{}d {:02}:{:02}:{:02}", days, hours, minutes, seconds)
+        } else if hours > 0 {
+            format!("{}:{:02}:{:02}", hours, minutes, seconds)
+        } else {
+            format!("{}:{:02}", minutes, seconds)
+        }
+    }
+}
@@ -1,30 +1,107 @@
 use pleco::core::sq::SQ as Sq;
 use pleco::core::Piece;
 use pleco::core::Player;
+use serde::Serialize;
+
+use time::OffsetDateTime;
+
+use super::{ApiClock, ApiMove};
 
 use crate::database::models::GameBoard;
+use crate::database::models::GameBot;
 use crate::database::models::GameOutcome;
+use crate::database::models::GamePlayer;
 use crate::database::models::GameStatus;
 use crate::database::models::GameWinner;
+use crate::database::models::TimeControl;
 use crate::database::types::DatabaseBoard as Board;
 
-#[derive(Clone)]
+#[derive(Clone, Debug, Serialize)]
 pub struct ApiGameBoard {
     pub game_id: String,
+    #[serde(rename = "fen")]
     pub board: Board,
     pub status: GameStatus,
     pub winner: Option<GameWinner>,
     pub outcome: Option<GameOutcome>,
+    pub draw_offer: Option<GamePlayer>,
+    pub takeback_request: Option<GamePlayer>,
+    /// Whether the game allows taking moves back at all, as `GameBoard::check_takeback` says
+    #[serde(skip)]
+    pub takebacks_allowed: bool,
+    /// The players who have a move on the board to take back
+    #[serde(skip)]
+    pub takeback_players: Vec<GamePlayer>,
+    pub white_seated: bool,
+    pub black_seated: bool,
+    pub white_player: Option<String>,
+    pub black_player: Option<String>,
+    pub bot: Option<GameBot>,
+    pub bot_player: Option<GamePlayer>,
+    /// Null for old moves whose notation couldn't be recovered
+    #[serde(rename = "history")]
+    pub moves: Vec<Option<String>>,
+    pub time_control: Option<TimeControl>,
+    pub white_clock: Option<ApiClock>,
+    pub black_clock: Option<ApiClock>,
 }
 
 impl From<GameBoard> for ApiGameBoard {
     fn from(game_board: GameBoard) -> Self {
+        let now = OffsetDateTime::now_utc();
+        let takeback_players = [GamePlayer::White, GamePlayer::Black]
+            .into_iter()
+            .filter(|player| game_board.has_move_to_take_back(*player))
+            .collect();
         Self {
             game_id: game_board.id().to_string(),
             board: game_board.board().clone(),
             status: game_board.status().clone(),
             winner: game_board.winner().clone(),
             outcome: game_board.outcome().clone(),
+            draw_offer: *game_board.draw_offer(),
+            takeback_request: *game_board.takeback_request(),
+            takebacks_allowed: game_board.check_takeback().is_ok(),
+            takeback_players,
+            white_seated: game_board.white_seated(),
+            black_seated: game_board.black_seated(),
+            white_player: game_board.white_player().clone(),
+            black_player: game_board.black_player().clone(),
+            bot: *game_board.bot(),
+            bot_player: *game_board.bot_player(),
+            moves: game_board.moves().to_vec(),
+            time_control: *game_board.time_control(),
+            white_clock: clock(&game_board, GamePlayer::White, now),
+            black_clock: clock(&game_board, GamePlayer::Black, now),
+        }
+    }
+}
+
+#[cfg(test)]
+impl ApiGameBoard {
+    /// The board of a new game nobody has sat down at, for tests that don't need the
+    ///  database
+    pub fn starting(game_id: &str) -> Self {
+        Self {
+            game_id: game_id.to_string(),
+            board: Board::new(),
+            status: GameStatus::Created,
+            winner: None,
+            outcome: None,
+            draw_offer: None,
+            takeback_request: None,
+            takebacks_allowed: true,
+            takeback_players: Vec::new(),
+            white_seated: false,
+            black_seated: false,
+            white_player: None,
+            black_player: None,
+            bot: None,
+            bot_player: None,
+            moves: Vec::new(),
+            time_control: None,
+            white_clock: None,
+            black_clock: None,
         }
     }
 }
@@ -34,13 +111,13 @@ impl ApiGameBoard {
         &self.game_id
     }
 
-    // TODO: I should consolidate these types
+    /// How many plies have been played to reach this board
+    pub fn ply(&self) -> u16 {
+        self.board.moves_played()
+    }
+
     pub fn turn(&self) -> String {
-        let player = match self.board.turn() {
-            Player::White => GameWinner::White,
-            Player::Black => GameWinner::Black,
-        };
-        player.to_string()
+        GamePlayer::from(self.board.turn()).to_string()
     }
 
     pub fn status(&self) -> String {
@@ -61,9 +138,89 @@ impl ApiGameBoard {
         }
     }
 
+    pub fn draw_offer(&self) -> Option<String> {
+        self.draw_offer.map(|player| player.to_string())
+    }
+
+    pub fn takeback_request(&self) -> Option<String> {
+        self.takeback_request.map(|player| player.to_string())
+    }
+
+    pub fn white_player(&self) -> String {
+        self.bot_name(GamePlayer::White)
+            .unwrap_or_else(|| seat_holder(&self.white_player, self.white_seated))
+    }
+
+    pub fn black_player(&self) -> String {
+        self.bot_name(GamePlayer::Black)
+            .unwrap_or_else(|| seat_holder(&self.black_player, self.black_seated))
+    }
+
+    /// The moves played so far in SAN, numbered and with the latest one marked. Moves
+    ///  without notation are shown as `?`.
+    pub fn move_list(&self) -> Vec<ApiMove> {
+        let last = self.moves.len().checked_sub(1);
+        self.moves
+            .iter()
+            .enumerate()
+            .map(|(ply, san)| {
+                let san = san.clone().unwrap_or_else(|| "?".to_string());
+                ApiMove::new(ply, san, Some(ply) == last)
+            })
+            .collect()
+    }
+
+    pub fn time_control(&self) -> Option<String> {
+        self.time_control
+            .map(|time_control| time_control.to_string())
+    }
+
+    pub fn white_clock(&self) -> &Option<ApiClock> {
+        &self.white_clock
+    }
+
+    pub fn black_clock(&self) -> &Option<ApiClock> {
+        &self.black_clock
+    }
+
+    /// Every legal move in the current position in UCI format -- none once the game is over
+    pub fn legal_moves(&self) -> Vec<String> {
+        match self.status {
+            GameStatus::Complete | GameStatus::Abandoned => Vec::new(),
+            GameStatus::Created | GameStatus::Active => self.board.legal_moves(),
+        }
+    }
+
+    pub fn takebacks_allowed(&self) -> bool {
+        self.takebacks_allowed
+    }
+
+    /// Name the bot if it sits on the given side
+    fn bot_name(&self, player: GamePlayer) -> Option<String> {
+        match (self.bot, self.bot_player) {
+            (Some(bot), Some(bot_player)) if bot_player == player => {
+                Some(format!("computer ({})", bot))
+            }
+            _ => None,
+        }
+    }
+
+    /// The seats no player has claimed yet
+    pub fn open_seats(&self) -> Vec<String> {
+        let mut seats = Vec::new();
+        if !self.white_seated {
+            seats.push(GamePlayer::White.to_string());
+        }
+        if !self.black_seated {
+            seats.push(GamePlayer::Black.to_string());
+        }
+        seats
+    }
+
     pub fn board_html(&self) -> String {
         // We'll just pass raw HTML to our template
         let mut html_board = String::new();
+        let legal_moves = self.legal_moves();
         html_board.push_str("<table class='chess-board'>");
 
         // Iterate over ranks to fully construct the board -- we need to populate every cell
@@ -90,14 +247,16 @@ impl ApiGameBoard {
                         // - class:
                         //  - chess-square-{light|dark}: the square's color
                         //  - chess-piece-{piece_char}: the occupying piece, if any. e.g. "chess-piece-P" for a white pawn
+                        // - data-legal-moves: the squares the occupying piece can move to, if any. e.g. "e3 e4"
                         match render_html_piece(piece) {
                             Some(piece_html) => {
                                 // Note: Since we know `piece` is `Some`, we can call .character_lossy() here
                                 html_board.push_str(&format!(
-                                    "<td id='{}' class='chess-square-{} chess-piece-{}'>{}</td>",
+                                    "<td id='{}' class='chess-square-{} chess-piece-{}'{}>{}</td>",
                                     id,
                                     color_class,
                                     piece.character_lossy(),
+                                    legal_moves_attr(&legal_moves, &id),
                                     piece_html
                                 ));
                             }
@@ -132,14 +291,16 @@ impl ApiGameBoard {
                         // - class:
                         //  - chess-square-{light|dark}: the square's color
                         //  - chess-piece-{piece_char}: the occupying piece, if any. e.g. "chess-piece-P" for a white pawn
+                        // - data-legal-moves: the squares the occupying piece can move to, if any. e.g. "e3 e4"
                         match render_html_piece(piece) {
                             Some(piece_html) => {
                                 // Note: Since we know `piece` is `Some`, we can call .character_lossy() here
                                 html_board.push_str(&format!(
-                                    "<td id='{}' class='chess-square-{} chess-piece-{}'>{}</td>",
+                                    "<td id='{}' class='chess-square-{} chess-piece-{}'{}>{}</td>",
                                     id,
                                     color_class,
                                     piece.character_lossy(),
+                                    legal_moves_attr(&legal_moves, &id),
                                     piece_html
                                 ));
                             }
@@ -161,6 +322,38 @@ impl ApiGameBoard {
     }
 }
 
+/// Read a side's clock as it stands right now, if the game is on the clock
+fn clock(game_board: &GameBoard, player: GamePlayer, now: OffsetDateTime) -> Option<ApiClock> {
+    game_board
+        .clock_ms(player, now)
+        .map(|clock_ms| ApiClock::new(clock_ms, game_board.clock_running(player)))
+}
+
+/// List the squares the piece on a square can move to, as a data attribute for `board.js`
+fn legal_moves_attr(legal_moves: &[String], square: &str) -> String {
+    let mut destinations: Vec<&str> = legal_moves
+        .iter()
+        .filter(|legal_move| legal_move.starts_with(square))
+        .map(|legal_move| &legal_move[2..4])
+        .collect();
+    // Promotions list the same square once for each piece
+    destinations.sort_unstable();
+    destinations.dedup();
+    if destinations.is_empty() {
+        return String::new();
+    }
+    format!(" data-legal-moves='{}'", destinations.join(" "))
+}
+
+/// Describe who holds a seat -- a username, an anonymous player, or nobody yet
+fn seat_holder(player: &Option<String>, seated: bool) -> String {
+    match (player, seated) {
+        (Some(username), _) => username.clone(),
+        (None, true) => "anonymous".to_string(),
+        (None, false) => "open".to_string(),
+    }
+}
+
 fn render_html_piece(piece: Piece) -> Option<String> {
     match piece {
         Piece::None => None,
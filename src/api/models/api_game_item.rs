@@ -1,13 +1,18 @@
+use serde::Serialize;
+
 use crate::database::models::Game;
 use crate::database::models::GameOutcome;
 use crate::database::models::GameStatus;
 use crate::database::models::GameWinner;
 
+#[derive(Serialize)]
 pub struct ApiGameItem {
     id: String,
     status: GameStatus,
     winner: Option<GameWinner>,
     outcome: Option<GameOutcome>,
+    white_player: Option<String>,
+    black_player: Option<String>,
 }
 
 impl ApiGameItem {
@@ -32,6 +37,20 @@ impl ApiGameItem {
             None => "None".to_string(),
         }
     }
+
+    pub fn white_player(&self) -> String {
+        match &self.white_player {
+            Some(username) => username.clone(),
+            None => "-".to_string(),
+        }
+    }
+
+    pub fn black_player(&self) -> String {
+        match &self.black_player {
+            Some(username) => username.clone(),
+            None => "-".to_string(),
+        }
+    }
 }
 
 impl From<Game> for ApiGameItem {
@@ -41,6 +60,8 @@ impl From<Game> for ApiGameItem {
             status: game.status().clone(),
             winner: game.winner().clone(),
             outcome: game.outcome().clone(),
+            white_player: game.white_player().clone(),
+            black_player: game.black_player().clone(),
         }
     }
 }
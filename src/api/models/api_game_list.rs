@@ -0,0 +1,21 @@
+use serde::Serialize;
+
+use super::ApiGameItem;
+
+use crate::database::models::{Game, GameCursor};
+
+/// A page of games, and the cursor to pass back for the next one if there are more
+#[derive(Serialize)]
+pub struct ApiGameList {
+    pub games: Vec<ApiGameItem>,
+    pub next_cursor: Option<String>,
+}
+
+impl ApiGameList {
+    pub fn new(games: Vec<Game>, next: Option<GameCursor>) -> Self {
+        Self {
+            games: games.into_iter().map(ApiGameItem::from).collect(),
+            next_cursor: next.map(|next| next.to_string()),
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::database::models::Game;
+use crate::database::models::GameBoard;
+use crate::database::models::GameError;
+use crate::database::models::GamePlayer;
+use crate::database::models::GameWinner;
+
+/// Movetext lines are wrapped to this width, as the PGN export format recommends
+const PGN_LINE_WIDTH: usize = 80;
+
+/// A game in Portable Game Notation, built from the moves recorded for it
+pub struct ApiGamePgn {
+    date: String,
+    white: String,
+    black: String,
+    result: String,
+    moves: Vec<String>,
+}
+
+impl ApiGamePgn {
+    /// Build the PGN for a game from its latest board, as returned by `GameBoard::latest`.
+    ///  Fails rather than write invalid movetext if any move is missing its notation.
+    pub fn new(game: &Game, latest: &GameBoard) -> Result<Self, GameError> {
+        let created_at = game.created_at();
+        let date = format!(
+            "{:04}.{:02}.{:02}",
+            created_at.year(),
+            created_at.month() as u8,
+            created_at.day()
+        );
+
+        let player_name = |username: &Option<String>, player: GamePlayer| match username {
+            Some(username) => username.clone(),
+            None => match (latest.bot(), latest.bot_player()) {
+                (Some(bot), Some(bot_player)) if *bot_player == player => {
+                    format!("computer ({})", bot)
+                }
+                _ => "?".to_string(),
+            },
+        };
+        let white = player_name(game.white_player(), GamePlayer::White);
+        let black = player_name(game.black_player(), GamePlayer::Black);
+
+        let result = match game.winner() {
+            Some(GameWinner::White) => "1-0",
+            Some(GameWinner::Black) => "0-1",
+            Some(GameWinner::Draw) => "1/2-1/2",
+            None => "*",
+        }
+        .to_string();
+
+        let moves = latest
+            .moves()
+            .iter()
+            .enumerate()
+            .map(|(ply, san)| san.clone().ok_or(GameError::MissingNotation(ply + 1)))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            date,
+            white,
+            black,
+            result,
+            moves,
+        })
+    }
+
+    /// Number the moves and wrap them into lines, ending with the result token
+    fn movetext(&self) -> String {
+        let mut tokens = Vec::new();
+        for (ply, san) in self.moves.iter().enumerate() {
+            if ply % 2 == 0 {
+                tokens.push(format!("{}.", ply / 2 + 1));
+            }
+            tokens.push(san.clone());
+        }
+        tokens.push(self.result.clone());
+
+        let mut lines = vec![String::new()];
+        for token in tokens {
+            let line = lines.last_mut().expect("there is always a line");
+            if line.is_empty() {
+                line.push_str(&token);
+            } else if line.len() + 1 + token.len() <= PGN_LINE_WIDTH {
+                line.push(' ');
+                line.push_str(&token);
+            } else {
+                lines.push(token);
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+impl Display for ApiGamePgn {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // The Seven Tag Roster, in its required order
+        writeln!(f, "[Event \"Krondor Chess game\"]")?;
+        writeln!(f, "[Site \"Krondor Chess\"]")?;
+        writeln!(f, "[Date \"{}\"]", self.date)?;
+        writeln!(f, "[Round \"-\"]")?;
+        writeln!(f, "[White \"{}\"]", escape_tag(&self.white))?;
+        writeln!(f, "[Black \"{}\"]", escape_tag(&self.black))?;
+        writeln!(f, "[Result \"{}\"]", self.result)?;
+        writeln!(f)?;
+        writeln!(f, "{}", self.movetext())
+    }
+}
+
+/// Tag values are quoted, so backslashes and quotes in them need escaping
+fn escape_tag(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,25 @@
+use serde::Serialize;
+
+use super::ApiGameBoard;
+
+use crate::database::models::GamePlayer;
+
+/// Everything an API client needs to play a game: the board, whose turn it is, and the
+///  moves they can make
+#[derive(Debug, Serialize)]
+pub struct ApiGameState {
+    #[serde(flatten)]
+    pub game: ApiGameBoard,
+    pub turn: GamePlayer,
+    pub legal_moves: Vec<String>,
+}
+
+impl From<ApiGameBoard> for ApiGameState {
+    fn from(game: ApiGameBoard) -> Self {
+        Self {
+            turn: GamePlayer::from(game.board.turn()),
+            legal_moves: game.legal_moves(),
+            game,
+        }
+    }
+}
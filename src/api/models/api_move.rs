@@ -0,0 +1,30 @@
+/// A move in a game's move list
+pub struct ApiMove {
+    ply: usize,
+    san: String,
+    last: bool,
+}
+
+impl ApiMove {
+    pub fn new(ply: usize, san: String, last: bool) -> Self {
+        Self { ply, san, last }
+    }
+
+    pub fn san(&self) -> &str {
+        &self.san
+    }
+
+    /// The move number printed before white's moves, e.g. `1.` -- blank for black's
+    pub fn number(&self) -> String {
+        if self.ply % 2 == 1 {
+            String::new()
+        } else {
+            format!("{}.", self.ply / 2 + 1)
+        }
+    }
+
+    /// Whether this is the most recent move in the game
+    pub fn is_last(&self) -> bool {
+        self.last
+    }
+}
@@ -0,0 +1,62 @@
+use sqlx::types::Uuid;
+
+use crate::api::models::ApiGameBoard;
+use crate::database::models::GamePlayer;
+
+/// Whoever is looking at a game board -- either a seated player or a spectator
+#[derive(Clone, Default)]
+pub struct ApiViewer {
+    pub token: Option<Uuid>,
+    pub seats: Vec<GamePlayer>,
+}
+
+impl ApiViewer {
+    pub fn is_spectator(&self) -> bool {
+        self.seats.is_empty()
+    }
+
+    pub fn seats(&self) -> String {
+        self.seats
+            .iter()
+            .map(|player| player.to_string())
+            .collect::<Vec<_>>()
+            .join(" and ")
+    }
+
+    /// A link the viewer can open elsewhere to take their seats with them
+    pub fn join_link(&self, game_id: &str) -> Option<String> {
+        self.token
+            .filter(|_| !self.is_spectator())
+            .map(|token| format!("/games/{}/join/{}", game_id, token))
+    }
+
+    /// Whether the viewer holds the seat of the player whose turn it is
+    pub fn can_move(&self, api_game_board: &ApiGameBoard) -> bool {
+        self.seats
+            .contains(&GamePlayer::from(api_game_board.board.turn()))
+    }
+
+    /// Whether the viewer can accept or decline the pending draw offer
+    pub fn can_respond_to_draw(&self, api_game_board: &ApiGameBoard) -> bool {
+        api_game_board
+            .draw_offer
+            .is_some_and(|offered_by| self.seats.contains(&offered_by.opponent()))
+    }
+
+    /// Whether the viewer has a move on the board they could ask to take back
+    pub fn can_request_takeback(&self, api_game_board: &ApiGameBoard) -> bool {
+        api_game_board.takebacks_allowed
+            && api_game_board.takeback_request.is_none()
+            && self
+                .seats
+                .iter()
+                .any(|player| api_game_board.takeback_players.contains(player))
+    }
+
+    /// Whether the viewer can accept or decline the pending takeback request
+    pub fn can_respond_to_takeback(&self, api_game_board: &ApiGameBoard) -> bool {
+        api_game_board
+            .takeback_request
+            .is_some_and(|requested_by| self.seats.contains(&requested_by.opponent()))
+    }
+}
@@ -1,5 +1,17 @@
+mod api_clock;
 mod api_game_board;
 mod api_game_item;
+mod api_game_list;
+mod api_game_pgn;
+mod api_game_state;
+mod api_move;
+mod api_viewer;
 
+pub use api_clock::ApiClock;
 pub use api_game_board::ApiGameBoard;
 pub use api_game_item::ApiGameItem;
+pub use api_game_list::ApiGameList;
+pub use api_game_pgn::ApiGamePgn;
+pub use api_game_state::ApiGameState;
+pub use api_move::ApiMove;
+pub use api_viewer::ApiViewer;
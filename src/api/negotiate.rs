@@ -0,0 +1,84 @@
+use std::convert::Infallible;
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header, request::Parts, HeaderValue},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+/// The representation a client asked for in its `Accept` header. Browsers and htmx get
+///  HTML unless they ask for JSON ahead of it.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Format {
+    #[default]
+    Html,
+    Json,
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Format {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let accept = parts
+            .headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','));
+        // Whichever of the two is listed first wins
+        for media_type in accept {
+            match media_type.split(';').next().map(str::trim) {
+                Some("application/json") => return Ok(Format::Json),
+                Some("text/html") => return Ok(Format::Html),
+                _ => {}
+            }
+        }
+        Ok(Format::Html)
+    }
+}
+
+impl Format {
+    /// Respond with a model, serialized as is for JSON or rendered by `html` otherwise
+    pub fn respond<M, F, H>(self, model: M, html: F) -> Negotiated<M, F>
+    where
+        M: Serialize,
+        F: FnOnce(M) -> H,
+        H: IntoResponse,
+    {
+        Negotiated {
+            format: self,
+            model,
+            html,
+        }
+    }
+}
+
+/// A response that can be either JSON or HTML, built from the same model
+pub struct Negotiated<M, F> {
+    format: Format,
+    model: M,
+    html: F,
+}
+
+impl<M, F, H> IntoResponse for Negotiated<M, F>
+where
+    M: Serialize,
+    F: FnOnce(M) -> H,
+    H: IntoResponse,
+{
+    fn into_response(self) -> Response {
+        let mut response = match self.format {
+            Format::Json => Json(self.model).into_response(),
+            Format::Html => (self.html)(self.model).into_response(),
+        };
+        // Caches need to know the same URL has more than one representation
+        response
+            .headers_mut()
+            .insert(header::VARY, HeaderValue::from_static("accept"));
+        response
+    }
+}
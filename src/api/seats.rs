@@ -0,0 +1,44 @@
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
+use sqlx::types::Uuid;
+use sqlx::PgConnection;
+
+use crate::api::models::ApiViewer;
+use crate::database::models::{Game, GameError};
+
+/// Name of the cookie holding a browser's seat token for a game
+fn seat_cookie_name(game_id: Uuid) -> String {
+    format!("seat-{}", game_id)
+}
+
+/// Build the cookie that hands a browser its seat token for a game
+pub fn seat_cookie(game_id: Uuid, token: Uuid) -> Cookie<'static> {
+    Cookie::build((seat_cookie_name(game_id), token.to_string()))
+        .path(format!("/games/{}", game_id))
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .permanent()
+        .build()
+}
+
+/// Read a browser's seat token for a game, if it has one
+pub fn seat_token(jar: &CookieJar, game_id: Uuid) -> Option<Uuid> {
+    jar.get(&seat_cookie_name(game_id))
+        .and_then(|cookie| Uuid::parse_str(cookie.value()).ok())
+}
+
+/// Work out which seats, if any, a browser holds in a game
+pub async fn viewer(
+    conn: &mut PgConnection,
+    jar: &CookieJar,
+    game_id: Uuid,
+) -> Result<ApiViewer, GameError> {
+    let token = match seat_token(jar, game_id) {
+        Some(token) => token,
+        None => return Ok(ApiViewer::default()),
+    };
+    let seats = Game::seats(conn, game_id, token).await?;
+    Ok(ApiViewer {
+        token: Some(token),
+        seats,
+    })
+}
@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::request::Parts,
+    response::{IntoResponse, Response},
+};
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
+use sqlx::types::Uuid;
+
+use crate::database::models::{Session, User, SESSION_LENGTH_DAYS};
+use crate::AppState;
+
+/// Name of the cookie holding a browser's login session id
+const SESSION_COOKIE_NAME: &str = "session";
+
+/// How often to clear out expired login sessions
+const EXPIRE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Build the cookie that hands a browser its login session
+pub fn session_cookie(session_id: Uuid) -> Cookie<'static> {
+    Cookie::build((SESSION_COOKIE_NAME, session_id.to_string()))
+        .path("/")
+        .http_only(true)
+        .secure(true)
+        .same_site(SameSite::Lax)
+        .max_age(time::Duration::days(SESSION_LENGTH_DAYS))
+        .build()
+}
+
+/// Build a cookie that clears a browser's login session
+pub fn removal_cookie() -> Cookie<'static> {
+    Cookie::build(SESSION_COOKIE_NAME).path("/").build()
+}
+
+/// Read a browser's login session id, if it has one
+pub fn session_id(jar: &CookieJar) -> Option<Uuid> {
+    jar.get(SESSION_COOKIE_NAME)
+        .and_then(|cookie| Uuid::parse_str(cookie.value()).ok())
+}
+
+/// Delete login sessions once they've expired in the background. They already stop working
+///  at `expires_at`; this just keeps the table from growing.
+pub fn spawn_expirer(state: AppState) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(EXPIRE_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = expire(&state).await {
+                tracing::error!("failed to expire sessions: {}", e);
+            }
+        }
+    });
+}
+
+async fn expire(state: &AppState) -> Result<(), sqlx::Error> {
+    let mut conn = state.database().acquire().await?;
+    let expired = Session::expire(&mut conn).await?;
+    if expired > 0 {
+        tracing::info!("expired {} sessions", expired);
+    }
+    Ok(())
+}
+
+/// The logged in user making a request. Handlers that also serve anonymous visitors
+///  should take an `Option<CurrentUser>`.
+pub struct CurrentUser(pub User);
+
+#[async_trait]
+impl FromRequestParts<AppState> for CurrentUser {
+    type Rejection = CurrentUserError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let jar = CookieJar::from_headers(&parts.headers);
+        let session_id = session_id(&jar).ok_or(CurrentUserError::NotLoggedIn)?;
+
+        let mut conn = state.database().acquire().await?;
+        match User::from_session(&mut conn, session_id).await? {
+            Some(user) => Ok(CurrentUser(user)),
+            None => Err(CurrentUserError::NotLoggedIn),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CurrentUserError {
+    #[error("sqlx error: {0}")]
+    Sqlx(#[from] sqlx::Error),
+    #[error("not logged in")]
+    NotLoggedIn,
+}
+
+impl IntoResponse for CurrentUserError {
+    fn into_response(self) -> Response {
+        match self {
+            CurrentUserError::NotLoggedIn => {
+                let body = format!("{}", self);
+                (axum::http::StatusCode::UNAUTHORIZED, body).into_response()
+            }
+            _ => {
+                let body = format!("{}", self);
+                (axum::http::StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
+            }
+        }
+    }
+}
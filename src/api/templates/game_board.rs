@@ -1,9 +1,10 @@
 use askama::Template;
 
-use crate::api::models::ApiGameBoard;
+use crate::api::models::{ApiGameBoard, ApiViewer};
 
 #[derive(Template, Clone)]
 #[template(path = "game_board.html")]
 pub struct GameBoardTemplate {
     pub api_game_board: ApiGameBoard,
+    pub viewer: ApiViewer,
 }
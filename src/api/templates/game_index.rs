@@ -1,9 +1,10 @@
 use askama::Template;
 
-use crate::api::models::ApiGameBoard;
+use crate::api::models::{ApiGameBoard, ApiViewer};
 
 #[derive(Template)]
 #[template(path = "game_index.html")]
 pub struct GameIndexTemplate {
     pub api_game_board: ApiGameBoard,
+    pub viewer: ApiViewer,
 }
@@ -0,0 +1,11 @@
+use askama::Template;
+
+use crate::api::models::ApiGameBoard;
+
+#[derive(Template)]
+#[template(path = "game_position.html")]
+pub struct GamePositionTemplate {
+    pub api_game_board: ApiGameBoard,
+    pub ply: i32,
+    pub last_ply: i32,
+}
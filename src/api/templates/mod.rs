@@ -1,5 +1,7 @@
 mod game_board;
 mod game_index;
+mod game_position;
 
 pub use game_board::GameBoardTemplate;
 pub use game_index::GameIndexTemplate;
+pub use game_position::GamePositionTemplate;
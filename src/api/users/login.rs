@@ -0,0 +1,51 @@
+use axum::{
+    extract::State,
+    response::{IntoResponse, Redirect, Response},
+    Form,
+};
+use axum_extra::extract::cookie::CookieJar;
+
+use crate::api::session::session_cookie;
+use crate::database::models::{Session, User, UserError};
+use crate::AppState;
+
+#[derive(serde::Deserialize, Debug)]
+pub struct LoginRequest {
+    username: String,
+    password: String,
+}
+
+pub async fn handler(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Form(request): Form<LoginRequest>,
+) -> Result<impl IntoResponse, LoginError> {
+    let mut conn = state.database().acquire().await?;
+    let user = User::authenticate(&mut conn, request.username.trim(), &request.password).await?;
+    let session_id = Session::create(&mut conn, user.id()).await?;
+
+    Ok((jar.add(session_cookie(session_id)), Redirect::to("/")))
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LoginError {
+    #[error("sqlx error: {0}")]
+    Sqlx(#[from] sqlx::Error),
+    #[error("user error: {0}")]
+    User(#[from] UserError),
+}
+
+impl IntoResponse for LoginError {
+    fn into_response(self) -> Response {
+        match self {
+            LoginError::User(UserError::InvalidCredentials) => {
+                let body = format!("{}", UserError::InvalidCredentials);
+                (axum::http::StatusCode::UNAUTHORIZED, body).into_response()
+            }
+            _ => {
+                let body = format!("{}", self);
+                (axum::http::StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
+            }
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use axum::{
+    extract::State,
+    response::{IntoResponse, Redirect, Response},
+};
+use axum_extra::extract::cookie::CookieJar;
+
+use crate::api::session::{removal_cookie, session_id};
+use crate::database::models::Session;
+use crate::AppState;
+
+pub async fn handler(
+    State(state): State<AppState>,
+    jar: CookieJar,
+) -> Result<impl IntoResponse, LogoutError> {
+    if let Some(session_id) = session_id(&jar) {
+        let mut conn = state.database().acquire().await?;
+        Session::delete(&mut conn, session_id).await?;
+    }
+
+    Ok((jar.remove(removal_cookie()), Redirect::to("/")))
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LogoutError {
+    #[error("sqlx error: {0}")]
+    Sqlx(#[from] sqlx::Error),
+}
+
+impl IntoResponse for LogoutError {
+    fn into_response(self) -> Response {
+        let body = format!("{}", self);
+        (axum::http::StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
+    }
+}
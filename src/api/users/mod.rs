@@ -0,0 +1,4 @@
+pub mod login;
+pub mod logout;
+pub mod read_login;
+pub mod register;
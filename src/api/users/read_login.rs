@@ -0,0 +1,16 @@
+use askama::Template;
+use axum::response::IntoResponse;
+
+use crate::api::session::CurrentUser;
+
+pub async fn handler(user: Option<CurrentUser>) -> impl IntoResponse {
+    LoginTemplate {
+        username: user.map(|CurrentUser(user)| user.username().to_string()),
+    }
+}
+
+#[derive(Template)]
+#[template(path = "login.html")]
+struct LoginTemplate {
+    username: Option<String>,
+}
@@ -0,0 +1,64 @@
+use axum::{
+    extract::State,
+    response::{IntoResponse, Redirect, Response},
+    Form,
+};
+use axum_extra::extract::cookie::CookieJar;
+
+use crate::api::session::session_cookie;
+use crate::database::models::{NewUser, Session, UserError};
+use crate::AppState;
+
+#[derive(serde::Deserialize, Debug)]
+pub struct RegisterRequest {
+    username: String,
+    password: String,
+}
+
+pub async fn handler(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Form(request): Form<RegisterRequest>,
+) -> Result<impl IntoResponse, RegisterError> {
+    // Hash before taking a connection, so a slow hash doesn't hold a transaction open
+    let new_user = NewUser::new(request.username.trim(), &request.password).await?;
+    let mut conn = state.database().begin().await?;
+    let user = new_user.create(&mut conn).await?;
+    let session_id = Session::create(&mut conn, user.id()).await?;
+    conn.commit().await?;
+
+    Ok((jar.add(session_cookie(session_id)), Redirect::to("/")))
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RegisterError {
+    #[error("sqlx error: {0}")]
+    Sqlx(#[from] sqlx::Error),
+    #[error("user error: {0}")]
+    User(#[from] UserError),
+}
+
+impl IntoResponse for RegisterError {
+    fn into_response(self) -> Response {
+        match self {
+            RegisterError::User(e) => match e {
+                UserError::InvalidUsername | UserError::WeakPassword => {
+                    let body = format!("{}", e);
+                    (axum::http::StatusCode::BAD_REQUEST, body).into_response()
+                }
+                UserError::UsernameTaken => {
+                    let body = format!("{}", e);
+                    (axum::http::StatusCode::CONFLICT, body).into_response()
+                }
+                _ => {
+                    let body = format!("internal server error: {}", e);
+                    (axum::http::StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
+                }
+            },
+            _ => {
+                let body = format!("{}", self);
+                (axum::http::StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
+            }
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use axum::{
+    extract::{rejection::PathRejection, Path, State},
+    http::{HeaderMap, StatusCode},
+    Extension, Json,
+};
+use serde::Serialize;
+use sqlx::types::Uuid;
+
+use super::make_move::seat_token;
+use super::ApiError;
+
+use crate::api::games::watch_game_sse::GameUpdateStream;
+use crate::api::models::{ApiGameBoard, ApiGameState, ApiViewer};
+use crate::api::session::CurrentUser;
+use crate::api::templates::GameBoardTemplate;
+use crate::database::models::{Game, GameBoard, GamePlayer};
+use crate::AppState;
+
+/// The game after taking the seat, with the token that now holds it. Pass the token back
+///  when making moves.
+#[derive(Serialize)]
+pub struct ClaimedSeat {
+    #[serde(flatten)]
+    game: ApiGameState,
+    seat_token: Uuid,
+}
+
+/// Send a `Seat-Token` header to take the seat with a token already holding the other one
+pub async fn handler(
+    State(state): State<AppState>,
+    Extension(tx): Extension<GameUpdateStream>,
+    path: Result<Path<(Uuid, String)>, PathRejection>,
+    user: Option<CurrentUser>,
+    headers: HeaderMap,
+) -> Result<Json<ClaimedSeat>, ApiError> {
+    let Path((game_id, player)) = path?;
+    let player = GamePlayer::try_from(player.as_str())
+        .map_err(|_| ApiError::new(StatusCode::BAD_REQUEST, format!("invalid seat: {}", player)))?;
+    let mut conn = state.database().begin().await?;
+    if !Game::exists(&mut conn, game_id).await? {
+        return Err(ApiError::not_found("game not found"));
+    }
+
+    let user_id = user.map(|CurrentUser(user)| user.id());
+    let token = Game::claim_seat(&mut conn, game_id, player, seat_token(&headers), user_id).await?;
+
+    let api_game_board = ApiGameBoard::from(GameBoard::latest(&mut conn, game_id).await?);
+    conn.commit().await?;
+
+    tx.send(
+        game_id,
+        GameBoardTemplate {
+            api_game_board: api_game_board.clone(),
+            viewer: ApiViewer::default(),
+        },
+    );
+
+    Ok(Json(ClaimedSeat {
+        game: ApiGameState::from(api_game_board),
+        seat_token: token,
+    }))
+}
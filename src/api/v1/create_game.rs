@@ -0,0 +1,52 @@
+use axum::{
+    extract::{rejection::JsonRejection, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use serde::Serialize;
+use sqlx::types::Uuid;
+
+use super::ApiError;
+
+use crate::api::games::create_game::{create, CreateGameRequest};
+use crate::api::games::watch_game_sse::GameUpdateStream;
+use crate::api::models::{ApiGameBoard, ApiGameState};
+use crate::api::session::CurrentUser;
+use crate::database::models::GameBoard;
+use crate::AppState;
+
+/// The new game, with the creator's seat token if they picked a side or are playing a bot.
+///  Pass the token back when making moves.
+#[derive(Serialize)]
+pub struct CreatedGame {
+    #[serde(flatten)]
+    game: ApiGameState,
+    seat_token: Option<Uuid>,
+}
+
+pub async fn handler(
+    State(state): State<AppState>,
+    Extension(tx): Extension<GameUpdateStream>,
+    user: Option<CurrentUser>,
+    request: Result<Json<CreateGameRequest>, JsonRejection>,
+) -> Result<(StatusCode, Json<CreatedGame>), ApiError> {
+    // Like the HTML form, a bare POST is a game between two people
+    let request = match request {
+        Ok(Json(request)) => request,
+        Err(JsonRejection::MissingJsonContentType(_)) => CreateGameRequest::default(),
+        Err(rejection) => return Err(rejection.into()),
+    };
+    let user_id = user.map(|CurrentUser(user)| user.id());
+    let (game, seat_token) = create(state.clone(), tx, user_id, &request).await?;
+
+    let mut conn = state.database().acquire().await?;
+    let game = ApiGameBoard::from(GameBoard::latest(&mut conn, game.id()).await?);
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedGame {
+            game: ApiGameState::from(game),
+            seat_token,
+        }),
+    ))
+}
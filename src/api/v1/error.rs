@@ -0,0 +1,177 @@
+use axum::{
+    extract::rejection::{JsonRejection, PathRejection, QueryRejection},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+use crate::api::games::create_game::CreateGameError;
+use crate::api::games::make_move::ReadBoardError;
+use crate::api::games::read_all_games::ReadAllGamesError;
+use crate::api::models::ApiGameState;
+use crate::database::models::GameError;
+
+/// An error as API clients see it, e.g.
+///  `{"error": {"code": "not_found", "message": "game not found"}}`. Conflicts also
+///  carry the current state of the game, and rejected moves say why they were rejected
+///  in `reason`.
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    message: String,
+    reason: Option<&'static str>,
+    game: Option<Box<ApiGameState>>,
+}
+
+#[derive(Serialize)]
+struct ApiErrorBody {
+    error: ApiErrorDetail,
+}
+
+#[derive(Serialize)]
+struct ApiErrorDetail {
+    code: &'static str,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    game: Option<Box<ApiGameState>>,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, message: impl ToString) -> Self {
+        Self {
+            status,
+            message: message.to_string(),
+            reason: None,
+            game: None,
+        }
+    }
+
+    pub fn not_found(message: impl ToString) -> Self {
+        Self::new(StatusCode::NOT_FOUND, message)
+    }
+
+    /// Reuse the status an HTML handler gives one of its errors, so both APIs agree
+    fn from_html(error: impl std::fmt::Display + IntoResponse) -> Self {
+        let message = error.to_string();
+        Self::new(error.into_response().status(), message)
+    }
+
+    /// A stable, machine-readable name for the kind of error
+    fn code(&self) -> &'static str {
+        match self.status {
+            StatusCode::BAD_REQUEST => "bad_request",
+            StatusCode::FORBIDDEN => "forbidden",
+            StatusCode::NOT_FOUND => "not_found",
+            StatusCode::CONFLICT => "conflict",
+            StatusCode::UNSUPPORTED_MEDIA_TYPE => "invalid_body",
+            StatusCode::UNPROCESSABLE_ENTITY => "unprocessable_entity",
+            _ => "internal_error",
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ApiErrorBody {
+            error: ApiErrorDetail {
+                code: self.code(),
+                message: self.message,
+                reason: self.reason,
+                game: self.game,
+            },
+        };
+        (self.status, Json(body)).into_response()
+    }
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(error: sqlx::Error) -> Self {
+        match error {
+            sqlx::Error::RowNotFound => Self::not_found("game not found"),
+            _ => Self::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("sqlx error: {}", error),
+            ),
+        }
+    }
+}
+
+/// Game errors get the same statuses the HTML handlers give them
+impl From<GameError> for ApiError {
+    fn from(error: GameError) -> Self {
+        let status = match error {
+            GameError::Sqlx(e) => return Self::from(e),
+            GameError::MissingNotation(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            GameError::InvalidMove(_, ref e) => {
+                return Self {
+                    reason: Some(e.reason()),
+                    ..Self::new(StatusCode::BAD_REQUEST, &error)
+                }
+            }
+            GameError::IllegalPgnMove(_, _, _)
+            | GameError::UnsupportedPgnStart(_)
+            | GameError::GameComplete
+            | GameError::GameAbandoned
+            | GameError::DrawAlreadyOffered
+            | GameError::NoDrawOffer
+            | GameError::TakebackAlreadyRequested
+            | GameError::NoTakebackRequest
+            | GameError::NothingToTakeBack(_)
+            | GameError::InvalidBotDepth(_) => StatusCode::BAD_REQUEST,
+            GameError::WrongSeat(_) => StatusCode::FORBIDDEN,
+            GameError::StaleMove(_, _) | GameError::TimedOut(_) | GameError::SeatTaken(_) => {
+                StatusCode::CONFLICT
+            }
+        };
+        Self::new(status, error)
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        Self::new(rejection.status(), rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for ApiError {
+    fn from(rejection: QueryRejection) -> Self {
+        Self::new(rejection.status(), rejection.body_text())
+    }
+}
+
+impl From<PathRejection> for ApiError {
+    fn from(rejection: PathRejection) -> Self {
+        Self::new(rejection.status(), rejection.body_text())
+    }
+}
+
+impl From<ReadAllGamesError> for ApiError {
+    fn from(error: ReadAllGamesError) -> Self {
+        Self::from_html(error)
+    }
+}
+
+impl From<CreateGameError> for ApiError {
+    fn from(error: CreateGameError) -> Self {
+        match error {
+            CreateGameError::Game(e) => Self::from(e),
+            _ => Self::from_html(error),
+        }
+    }
+}
+
+impl From<ReadBoardError> for ApiError {
+    fn from(error: ReadBoardError) -> Self {
+        match error {
+            ReadBoardError::Conflict(e, game) => Self {
+                game: Some(game),
+                ..Self::new(StatusCode::CONFLICT, e)
+            },
+            ReadBoardError::Game(e) => Self::from(e),
+            _ => Self::from_html(error),
+        }
+    }
+}
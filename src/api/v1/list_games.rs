@@ -0,0 +1,24 @@
+use axum::{
+    extract::{rejection::QueryRejection, Query, State},
+    Json,
+};
+
+use super::ApiError;
+
+use crate::api::games::read_all_games::ReadAllGamesRequest;
+use crate::api::models::ApiGameList;
+use crate::database::models::Game;
+use crate::AppState;
+
+/// Takes the same filters, ordering and cursor as the HTML game list
+pub async fn handler(
+    State(state): State<AppState>,
+    request: Result<Query<ReadAllGamesRequest>, QueryRejection>,
+) -> Result<Json<ApiGameList>, ApiError> {
+    let Query(request) = request?;
+    let query = request.query()?;
+    let mut conn = state.database().acquire().await?;
+    let (games, next) = Game::read_page(&mut conn, &query).await?;
+
+    Ok(Json(ApiGameList::new(games, next)))
+}
@@ -0,0 +1,62 @@
+use axum::{
+    extract::{
+        rejection::{JsonRejection, PathRejection},
+        Path, State,
+    },
+    http::HeaderMap,
+    Extension, Json,
+};
+use sqlx::types::Uuid;
+
+use super::ApiError;
+
+use crate::api::games::make_move::{idempotency_key, make_move, MoveAttempt};
+use crate::api::games::watch_game_sse::GameUpdateStream;
+use crate::api::models::ApiGameState;
+use crate::AppState;
+
+/// Header clients can send their seat token in, instead of the `seat_token` field
+pub const SEAT_TOKEN_HEADER: &str = "seat-token";
+
+/// Read the seat token a request was sent with, if it has a valid one
+pub fn seat_token(headers: &HeaderMap) -> Option<Uuid> {
+    headers
+        .get(SEAT_TOKEN_HEADER)
+        .and_then(|token| token.to_str().ok())
+        .and_then(|token| Uuid::parse_str(token).ok())
+}
+
+/// The seat token is the one handed out when the game was created or the seat claimed, sent
+///  in the body or a `Seat-Token` header. Give the ply the board was at when the move was
+///  picked to have it refused if the game has moved on since, and an `Idempotency-Key`
+///  header to make retrying the request safe.
+#[derive(serde::Deserialize, Debug)]
+pub struct MakeMoveRequest {
+    #[serde(default)]
+    uci_move: String,
+    #[serde(default)]
+    resign: bool,
+    seat_token: Option<Uuid>,
+    ply: Option<u16>,
+}
+
+pub async fn handler(
+    State(state): State<AppState>,
+    Extension(tx): Extension<GameUpdateStream>,
+    game_id: Result<Path<Uuid>, PathRejection>,
+    headers: HeaderMap,
+    request: Result<Json<MakeMoveRequest>, JsonRejection>,
+) -> Result<Json<ApiGameState>, ApiError> {
+    let Path(game_id) = game_id?;
+    let Json(request) = request?;
+    let idempotency_key = idempotency_key(&headers);
+    let attempt = MoveAttempt {
+        uci_move: &request.uci_move,
+        resign: request.resign,
+        ply: request.ply,
+        idempotency_key: idempotency_key.as_deref(),
+    };
+    let seat_token = request.seat_token.or_else(|| seat_token(&headers));
+    let game = make_move(state, tx, game_id, seat_token, attempt).await?;
+    Ok(Json(ApiGameState::from(game)))
+}
@@ -0,0 +1,28 @@
+//! A versioned JSON API for bots and scripts, alongside the HTML the browser uses
+
+use axum::{
+    routing::{get, post},
+    Router,
+};
+
+use crate::AppState;
+
+pub mod claim_seat;
+pub mod create_game;
+pub mod error;
+pub mod list_games;
+pub mod make_move;
+pub mod read_game;
+
+pub use error::ApiError;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/games",
+            get(list_games::handler).post(create_game::handler),
+        )
+        .route("/games/:game_id", get(read_game::handler))
+        .route("/games/:game_id/moves", post(make_move::handler))
+        .route("/games/:game_id/seats/:player", post(claim_seat::handler))
+}
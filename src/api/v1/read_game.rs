@@ -0,0 +1,25 @@
+use axum::{
+    extract::{rejection::PathRejection, Path, State},
+    Json,
+};
+use sqlx::types::Uuid;
+
+use super::ApiError;
+
+use crate::api::models::{ApiGameBoard, ApiGameState};
+use crate::database::models::{Game, GameBoard};
+use crate::AppState;
+
+pub async fn handler(
+    State(state): State<AppState>,
+    game_id: Result<Path<Uuid>, PathRejection>,
+) -> Result<Json<ApiGameState>, ApiError> {
+    let Path(game_id) = game_id?;
+    let mut conn = state.database().acquire().await?;
+    if !Game::exists(&mut conn, game_id).await? {
+        return Err(ApiError::not_found("game not found"));
+    }
+
+    let game = ApiGameBoard::from(GameBoard::latest(&mut conn, game_id).await?);
+    Ok(Json(ApiGameState::from(game)))
+}
@@ -2,34 +2,203 @@ use pleco::core::Player;
 use sqlx::types::Uuid;
 use sqlx::FromRow;
 use sqlx::PgConnection;
-use sqlx::PgPool;
 use time::OffsetDateTime;
 
+use super::game_bot::{GameBot, MAX_BOT_DEPTH, MIN_BOT_DEPTH};
+use super::game_list::{GameCursor, GameListQuery, GameOrder};
 use super::game_outcome::GameOutcome;
+use super::game_pgn::PgnGame;
+use super::game_player::GamePlayer;
 use super::game_status::GameStatus;
 use super::game_winner::GameWinner;
+use super::time_control::TimeControl;
 
 use crate::database::types::DatabaseBoard as Board;
+use crate::database::types::MoveError;
 
 pub struct NewGame;
 
 impl NewGame {
-    pub async fn create(conn: &PgPool) -> Result<Game, sqlx::Error> {
+    /// Create a game between two people, on the clock if a time control is given
+    pub async fn create(
+        conn: &mut PgConnection,
+        time_control: Option<TimeControl>,
+    ) -> Result<Game, sqlx::Error> {
+        let game = sqlx::query_as!(
+            Game,
+            r#"INSERT INTO games (time_control, white_clock_ms, black_clock_ms)
+            VALUES ($1, $2, $2)
+            RETURNING
+                id as "id: Uuid",
+                created_at as "created_at: OffsetDateTime",
+                updated_at as "updated_at: OffsetDateTime",
+                status as "status: GameStatus",
+                winner as "winner: GameWinner",
+                outcome as "outcome: GameOutcome",
+                NULL::VARCHAR as "white_player?",
+                NULL::VARCHAR as "black_player?"
+            "#,
+            time_control.map(|time_control| time_control.to_string()),
+            time_control.map(|time_control| time_control.initial_ms()),
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+        Ok(game)
+    }
+
+    /// Create a game against one of pleco's bots, seating the bot on the given side
+    pub async fn create_against_bot(
+        conn: &mut PgConnection,
+        bot: GameBot,
+        depth: i16,
+        bot_player: GamePlayer,
+        time_control: Option<TimeControl>,
+    ) -> Result<Game, GameError> {
+        if !(MIN_BOT_DEPTH..=MAX_BOT_DEPTH).contains(&depth) {
+            return Err(GameError::InvalidBotDepth(depth));
+        }
+
+        // The bot's seat gets a token nobody else holds, so it plays through the same
+        //  seat checks as everyone else
         let game = sqlx::query_as!(
             Game,
-            r#"INSERT INTO games DEFAULT VALUES RETURNING
+            r#"INSERT INTO games (
+                bot,
+                bot_depth,
+                bot_player,
+                white_token,
+                black_token,
+                time_control,
+                white_clock_ms,
+                black_clock_ms
+            )
+            VALUES (
+                $1,
+                $2,
+                $3::VARCHAR,
+                CASE WHEN $3::VARCHAR = 'white' THEN uuid_generate_v4() END,
+                CASE WHEN $3::VARCHAR = 'black' THEN uuid_generate_v4() END,
+                $4,
+                $5,
+                $5
+            )
+            RETURNING
                 id as "id: Uuid",
                 created_at as "created_at: OffsetDateTime",
                 updated_at as "updated_at: OffsetDateTime",
                 status as "status: GameStatus",
                 winner as "winner: GameWinner",
-                outcome as "outcome: GameOutcome"
+                outcome as "outcome: GameOutcome",
+                NULL::VARCHAR as "white_player?",
+                NULL::VARCHAR as "black_player?"
             "#,
+            bot.to_string(),
+            depth,
+            bot_player.to_string(),
+            time_control.map(|time_control| time_control.to_string()),
+            time_control.map(|time_control| time_control.initial_ms()),
         )
-        .fetch_one(conn)
+        .fetch_one(&mut *conn)
         .await?;
         Ok(game)
     }
+
+    /// Create a game from a PGN score, replaying its moves so every position is recorded
+    ///  and mapping its result onto the game. Players' names are kept as they're given,
+    ///  without linking them to accounts, since anyone can write any name into a PGN.
+    ///  `index` is the game's place in its file, used to report errors.
+    pub async fn import(
+        conn: &mut PgConnection,
+        index: usize,
+        pgn_game: &PgnGame,
+    ) -> Result<Game, GameError> {
+        // Every game here starts from the standard position
+        if pgn_game.tag("FEN").is_some() {
+            return Err(GameError::UnsupportedPgnStart(index));
+        }
+
+        // PGN marks unknown players with a question mark
+        let player_name = |tag| {
+            pgn_game
+                .tag(tag)
+                .filter(|name| !name.is_empty() && *name != "?")
+        };
+        let game_id = sqlx::query_scalar!(
+            r#"INSERT INTO games (white_name, black_name)
+            VALUES ($1, $2)
+            RETURNING id as "id: Uuid"
+            "#,
+            player_name("White"),
+            player_name("Black"),
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+
+        let mut board = Board::new();
+        let mut repetition_keys = vec![board.repetition_key()];
+        for (ply, san) in pgn_game.moves().iter().enumerate() {
+            let illegal_move = || GameError::IllegalPgnMove(index, ply + 1, san.clone());
+            let bit_move = board.san_move(san).ok_or_else(illegal_move)?;
+            let move_number = board.moves_played() as i32;
+            let (uci, san) = (bit_move.stringify(), board.san(bit_move));
+            if !board.apply_uci_move(&uci) {
+                return Err(illegal_move());
+            }
+            GameBoard::record_move(conn, game_id, move_number, &board, &uci, &san).await?;
+            repetition_keys.push(board.repetition_key());
+        }
+
+        // Decisive results without a mate on the board are taken as resignations, and
+        //  draws without a rule that forced them as agreed
+        let repetitions = repetition_keys
+            .iter()
+            .filter(|key| **key == board.repetition_key())
+            .count();
+        let decisive_outcome = if board.checkmate() {
+            GameOutcome::Checkmate
+        } else {
+            GameOutcome::Resignation
+        };
+        let result = match pgn_game.result() {
+            "1-0" => Some((GameWinner::White, decisive_outcome)),
+            "0-1" => Some((GameWinner::Black, decisive_outcome)),
+            "1/2-1/2" => {
+                let draw_outcome = if board.stalemate() {
+                    GameOutcome::Stalemate
+                } else if board.insufficient_material() {
+                    GameOutcome::InsufficientMaterial
+                } else if repetitions >= 3 {
+                    GameOutcome::ThreefoldRepetition
+                } else if board.rule_50() >= 100 {
+                    GameOutcome::FiftyMoveRule
+                } else {
+                    GameOutcome::Agreement
+                };
+                Some((GameWinner::Draw, draw_outcome))
+            }
+            _ => None,
+        };
+
+        match result {
+            Some((game_winner, game_outcome)) => {
+                GameBoard::complete(conn, game_id, game_winner, game_outcome).await?;
+            }
+            None if !pgn_game.moves().is_empty() => {
+                sqlx::query!(
+                    r#"UPDATE games
+                    SET status = 'active'
+                    WHERE id = $1
+                    "#,
+                    game_id,
+                )
+                .execute(&mut *conn)
+                .await?;
+            }
+            None => {}
+        }
+
+        Game::read(conn, game_id).await
+    }
 }
 
 #[allow(dead_code)]
@@ -41,6 +210,8 @@ pub struct Game {
     status: GameStatus,
     winner: Option<GameWinner>,
     outcome: Option<GameOutcome>,
+    white_player: Option<String>,
+    black_player: Option<String>,
 }
 
 impl Game {
@@ -48,6 +219,10 @@ impl Game {
         self.id
     }
 
+    pub fn created_at(&self) -> &OffsetDateTime {
+        &self.created_at
+    }
+
     pub fn status(&self) -> &GameStatus {
         &self.status
     }
@@ -60,6 +235,14 @@ impl Game {
         &self.outcome
     }
 
+    pub fn white_player(&self) -> &Option<String> {
+        &self.white_player
+    }
+
+    pub fn black_player(&self) -> &Option<String> {
+        &self.black_player
+    }
+
     // TODO: make the state machine more robust -- but maybe eventually this will
     //  check if a user has access to a game
     /// Check if a game exists in the database
@@ -70,25 +253,181 @@ impl Game {
         Ok(maybe_game.is_some())
     }
 
-    // TODO: pagination
-    /// Read all games from the database
-    pub async fn read_all(conn: &mut PgConnection) -> Result<Vec<Game>, GameError> {
-        let games = sqlx::query_as!(
-            Game,
+    /// Claim an open seat in a game, returning the secret token that now holds it. An
+    ///  existing token is reused if given, so one browser can hold both seats. The seat
+    ///  is recorded against the user claiming it, if they're logged in.
+    pub async fn claim_seat(
+        conn: &mut PgConnection,
+        game_id: Uuid,
+        player: GamePlayer,
+        token: Option<Uuid>,
+        user_id: Option<Uuid>,
+    ) -> Result<Uuid, GameError> {
+        let maybe_token = match player {
+            GamePlayer::White => {
+                sqlx::query_scalar!(
+                    r#"UPDATE games
+                    SET white_token = COALESCE($2, uuid_generate_v4()),
+                        white_user_id = $3
+                    WHERE id = $1
+                    AND white_token IS NULL
+                    RETURNING white_token as "token!: Uuid"
+                    "#,
+                    game_id,
+                    token,
+                    user_id,
+                )
+                .fetch_optional(&mut *conn)
+                .await?
+            }
+            GamePlayer::Black => {
+                sqlx::query_scalar!(
+                    r#"UPDATE games
+                    SET black_token = COALESCE($2, uuid_generate_v4()),
+                        black_user_id = $3
+                    WHERE id = $1
+                    AND black_token IS NULL
+                    RETURNING black_token as "token!: Uuid"
+                    "#,
+                    game_id,
+                    token,
+                    user_id,
+                )
+                .fetch_optional(&mut *conn)
+                .await?
+            }
+        };
+
+        maybe_token.ok_or(GameError::SeatTaken(player))
+    }
+
+    /// Read which seats in a game are held by the given token
+    pub async fn seats(
+        conn: &mut PgConnection,
+        game_id: Uuid,
+        token: Uuid,
+    ) -> Result<Vec<GamePlayer>, GameError> {
+        let seats = sqlx::query!(
             r#"SELECT
-                id as "id: Uuid",
-                created_at as "created_at: OffsetDateTime",
-                updated_at as "updated_at: OffsetDateTime",
-                status as "status: GameStatus",
-                winner as "winner: GameWinner",
-                outcome as "outcome: GameOutcome"
+                COALESCE(white_token = $2, false) as "white!",
+                COALESCE(black_token = $2, false) as "black!"
             FROM games
-            ORDER BY created_at DESC
+            WHERE id = $1
+            "#,
+            game_id,
+            token,
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+
+        let mut players = Vec::new();
+        if seats.white {
+            players.push(GamePlayer::White);
+        }
+        if seats.black {
+            players.push(GamePlayer::Black);
+        }
+        Ok(players)
+    }
+
+    /// Read a single game -- assumes the game exists
+    pub async fn read(conn: &mut PgConnection, game_id: Uuid) -> Result<Game, GameError> {
+        let game = sqlx::query_as!(
+            Game,
+            r#"SELECT
+                g.id as "id: Uuid",
+                g.created_at as "created_at: OffsetDateTime",
+                g.updated_at as "updated_at: OffsetDateTime",
+                g.status as "status: GameStatus",
+                g.winner as "winner: GameWinner",
+                g.outcome as "outcome: GameOutcome",
+                COALESCE(wu.username, g.white_name) as "white_player?",
+                COALESCE(bu.username, g.black_name) as "black_player?"
+            FROM games as g
+            LEFT JOIN users as wu ON wu.id = g.white_user_id
+            LEFT JOIN users as bu ON bu.id = g.black_user_id
+            WHERE g.id = $1
             "#,
+            game_id,
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+        Ok(game)
+    }
+
+    /// Read a page of games matching a query, along with the cursor for the page after it
+    ///  if there are more
+    pub async fn read_page(
+        conn: &mut PgConnection,
+        query: &GameListQuery,
+    ) -> Result<(Vec<Game>, Option<GameCursor>), GameError> {
+        // Pages are ordered by the sort time, then by id to break ties, so a cursor of both
+        //  picks up exactly where the last page left off
+        let mut games = sqlx::query_as!(
+            Game,
+            r#"SELECT
+                g.id as "id!: Uuid",
+                g.created_at as "created_at!: OffsetDateTime",
+                g.updated_at as "updated_at!: OffsetDateTime",
+                g.status as "status!: GameStatus",
+                g.winner as "winner: GameWinner",
+                g.outcome as "outcome: GameOutcome",
+                COALESCE(wu.username, g.white_name) as "white_player?",
+                COALESCE(bu.username, g.black_name) as "black_player?"
+            FROM games as g
+            LEFT JOIN users as wu ON wu.id = g.white_user_id
+            LEFT JOIN users as bu ON bu.id = g.black_user_id
+            CROSS JOIN LATERAL (
+                SELECT CASE WHEN $1 = 'activity' THEN g.updated_at ELSE g.created_at END as at
+            ) as sort
+            WHERE ($3::VARCHAR IS NULL OR g.status = $3)
+            AND ($4::VARCHAR IS NULL OR g.winner = $4)
+            AND ($5::VARCHAR IS NULL OR g.outcome = $5)
+            AND (
+                $6::BIGINT IS NULL
+                OR ($2 = 'newest' AND (sort.at, g.id) < (
+                    TIMESTAMP 'epoch' + $6 * INTERVAL '1 microsecond', $7::UUID
+                ))
+                OR ($2 = 'oldest' AND (sort.at, g.id) > (
+                    TIMESTAMP 'epoch' + $6 * INTERVAL '1 microsecond', $7::UUID
+                ))
+            )
+            ORDER BY
+                CASE WHEN $2 = 'newest' THEN sort.at END DESC,
+                CASE WHEN $2 = 'newest' THEN g.id END DESC,
+                CASE WHEN $2 = 'oldest' THEN sort.at END ASC,
+                CASE WHEN $2 = 'oldest' THEN g.id END ASC
+            LIMIT $8
+            "#,
+            query.order.to_string(),
+            query.direction.to_string(),
+            query.status.as_ref().map(|status| status.to_string()),
+            query.winner.as_ref().map(|winner| winner.to_string()),
+            query.outcome.as_ref().map(|outcome| outcome.to_string()),
+            query.after.map(|after| after.micros()),
+            query.after.map(|after| after.id()),
+            // Read one extra game to tell whether there's another page
+            query.limit + 1,
         )
         .fetch_all(&mut *conn)
         .await?;
-        Ok(games)
+
+        let next = if games.len() as i64 > query.limit {
+            games.truncate(query.limit as usize);
+            games.last().map(|game| game.cursor(query.order))
+        } else {
+            None
+        };
+        Ok((games, next))
+    }
+
+    /// Where a page ending with this game leaves off
+    fn cursor(&self, order: GameOrder) -> GameCursor {
+        let at = match order {
+            GameOrder::Created => self.created_at,
+            GameOrder::Activity => self.updated_at,
+        };
+        GameCursor::new(at, self.id)
     }
 }
 
@@ -100,6 +439,19 @@ pub struct GameBoard {
     status: GameStatus,
     winner: Option<GameWinner>,
     outcome: Option<GameOutcome>,
+    draw_offer: Option<GamePlayer>,
+    takeback_request: Option<GamePlayer>,
+    white_seated: bool,
+    black_seated: bool,
+    white_player: Option<String>,
+    black_player: Option<String>,
+    bot: Option<GameBot>,
+    bot_player: Option<GamePlayer>,
+    moves: Vec<Option<String>>,
+    time_control: Option<TimeControl>,
+    white_clock_ms: Option<i64>,
+    black_clock_ms: Option<i64>,
+    clock_started_at: Option<OffsetDateTime>,
 }
 
 impl GameBoard {
@@ -124,94 +476,688 @@ impl GameBoard {
         &self.outcome
     }
 
+    pub fn draw_offer(&self) -> &Option<GamePlayer> {
+        &self.draw_offer
+    }
+
+    pub fn takeback_request(&self) -> &Option<GamePlayer> {
+        &self.takeback_request
+    }
+
+    pub fn white_seated(&self) -> bool {
+        self.white_seated
+    }
+
+    pub fn black_seated(&self) -> bool {
+        self.black_seated
+    }
+
+    pub fn white_player(&self) -> &Option<String> {
+        &self.white_player
+    }
+
+    pub fn black_player(&self) -> &Option<String> {
+        &self.black_player
+    }
+
+    pub fn bot(&self) -> &Option<GameBot> {
+        &self.bot
+    }
+
+    pub fn bot_player(&self) -> &Option<GamePlayer> {
+        &self.bot_player
+    }
+
+    /// The moves played to reach this board, in SAN. Moves recorded before notation was
+    ///  stored whose notation couldn't be derived from their positions have none.
+    pub fn moves(&self) -> &[Option<String>] {
+        &self.moves
+    }
+
+    pub fn time_control(&self) -> &Option<TimeControl> {
+        &self.time_control
+    }
+
+    /// A player's remaining time right now, if the game is on the clock. Only the clock
+    ///  of the player to move runs, and only once the first move has been made.
+    pub fn clock_ms(&self, player: GamePlayer, now: OffsetDateTime) -> Option<i64> {
+        let remaining_ms = match player {
+            GamePlayer::White => self.white_clock_ms?,
+            GamePlayer::Black => self.black_clock_ms?,
+        };
+        match self.clock_started_at {
+            Some(started_at) if self.clock_running(player) => {
+                Some(remaining_ms - (now - started_at).whole_milliseconds() as i64)
+            }
+            _ => Some(remaining_ms),
+        }
+    }
+
+    /// Refuse to take moves back in a game that has been abandoned, or that a player or
+    ///  the clock ended. Games the board ended can be picked up again from before the end.
+    pub fn check_takeback(&self) -> Result<(), GameError> {
+        match (&self.status, &self.outcome) {
+            (GameStatus::Complete, Some(outcome)) if outcome.decided_on_board() => Ok(()),
+            _ => self.check_playable(),
+        }
+    }
+
+    /// How many plies taking back a player's last move undoes -- their opponent's reply
+    ///  goes too if it's been made, so it's the player's turn again
+    pub fn takeback_plies(&self, player: GamePlayer) -> u16 {
+        if GamePlayer::from(self.board.turn()) == player {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// Whether a player has made a move that could be taken back
+    pub fn has_move_to_take_back(&self, player: GamePlayer) -> bool {
+        self.takeback_plies(player) <= self.board.moves_played()
+    }
+
+    /// Refuse to play on in a game that has finished or been abandoned
+    pub fn check_playable(&self) -> Result<(), GameError> {
+        match self.status {
+            GameStatus::Complete => Err(GameError::GameComplete),
+            GameStatus::Abandoned => Err(GameError::GameAbandoned),
+            GameStatus::Created | GameStatus::Active => Ok(()),
+        }
+    }
+
+    /// Whether a player's clock is counting down
+    pub fn clock_running(&self, player: GamePlayer) -> bool {
+        self.check_playable().is_ok()
+            && self.clock_started_at.is_some()
+            && GamePlayer::from(self.board.turn()) == player
+    }
+
     /* Database Operations */
 
     /// Return the latest board for a game -- assumes the game exists
     pub async fn latest(conn: &mut PgConnection, game_id: Uuid) -> Result<Self, GameError> {
-        let maybe_game = sqlx::query_as!(
+        // Games without any moves yet are still in the starting position
+        let start_board = Board::new().fen();
+        let game = sqlx::query_as!(
             Self,
             r#"SELECT
                 g.id as "id: Uuid",
-                p.board as "board: Board",
+                COALESCE(p.board, $2) as "board!: Board",
                 g.status as "status: GameStatus",
                 g.winner as "winner: GameWinner",
-                g.outcome as "outcome: GameOutcome"
-            FROM positions as p
-            JOIN moves as m ON m.position_id = p.id
-            JOIN games as g ON g.id = m.game_id
+                g.outcome as "outcome: GameOutcome",
+                g.draw_offer as "draw_offer: GamePlayer",
+                g.takeback_request as "takeback_request: GamePlayer",
+                g.white_token IS NOT NULL as "white_seated!",
+                g.black_token IS NOT NULL as "black_seated!",
+                COALESCE(wu.username, g.white_name) as "white_player?",
+                COALESCE(bu.username, g.black_name) as "black_player?",
+                g.bot as "bot: GameBot",
+                g.bot_player as "bot_player: GamePlayer",
+                ARRAY(
+                    SELECT gm.san
+                    FROM moves as gm
+                    WHERE gm.game_id = g.id
+                    ORDER BY gm.move_number ASC
+                ) as "moves!: Vec<Option<String>>",
+                g.time_control as "time_control: TimeControl",
+                g.white_clock_ms,
+                g.black_clock_ms,
+                g.clock_started_at as "clock_started_at: OffsetDateTime"
+            FROM games as g
+            LEFT JOIN moves as m ON m.game_id = g.id
+            LEFT JOIN positions as p ON p.id = m.position_id
+            LEFT JOIN users as wu ON wu.id = g.white_user_id
+            LEFT JOIN users as bu ON bu.id = g.black_user_id
             WHERE g.id = $1
-            ORDER BY m.move_number DESC
+            ORDER BY m.move_number DESC NULLS LAST
             LIMIT 1
             "#,
             game_id,
+            start_board,
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(game)
+    }
+
+    /// Return every board a game has passed through after the given ply, ending with the
+    ///  latest board -- assumes the game exists. Only the latest board carries the game's
+    ///  current status, earlier boards are reported as active.
+    pub async fn since(
+        conn: &mut PgConnection,
+        game_id: Uuid,
+        ply: i32,
+    ) -> Result<Vec<Self>, GameError> {
+        // Moves are numbered by the ply they were played from
+        let mut boards = sqlx::query_scalar!(
+            r#"SELECT
+                p.board as "board: Board"
+            FROM positions as p
+            JOIN moves as m ON m.position_id = p.id
+            WHERE m.game_id = $1
+            AND m.move_number >= $2
+            ORDER BY m.move_number ASC
+            "#,
+            game_id,
+            ply,
         )
-        .fetch_optional(&mut *conn)
+        .fetch_all(&mut *conn)
         .await?;
 
-        match maybe_game {
-            Some(game) => Ok(game),
-            None => Ok(Self {
-                id: game_id,
-                board: Board::new(),
-                status: GameStatus::Created,
-                winner: None,
-                outcome: None,
-            }),
+        // The final board is replaced by the latest, which includes the game's status
+        let latest = Self::latest(conn, game_id).await?;
+        boards.pop();
+        let mut history = boards
+            .into_iter()
+            .map(|board| latest.rewound(board))
+            .collect::<Vec<_>>();
+        history.push(latest);
+        Ok(history)
+    }
+
+    /// Return the board as it stood after the given ply, or `None` if the game hasn't
+    ///  reached it -- assumes the game exists. Past boards are reported as active, like
+    ///  those from `since`.
+    pub async fn at(
+        conn: &mut PgConnection,
+        game_id: Uuid,
+        ply: i32,
+    ) -> Result<Option<Self>, GameError> {
+        let latest = Self::latest(conn, game_id).await?;
+        let latest_ply = latest.board.moves_played() as i32;
+        if ply < 0 || ply > latest_ply {
+            return Ok(None);
+        } else if ply == latest_ply {
+            return Ok(Some(latest));
+        } else if ply == 0 {
+            return Ok(Some(latest.rewound(Board::new())));
         }
+
+        // Moves are numbered by the ply they were played from
+        let board = sqlx::query_scalar!(
+            r#"SELECT
+                p.board as "board: Board"
+            FROM positions as p
+            JOIN moves as m ON m.position_id = p.id
+            WHERE m.game_id = $1
+            AND m.move_number = $2
+            "#,
+            game_id,
+            ply - 1,
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+        Ok(Some(latest.rewound(board)))
     }
 
-    /// Make a move in a game. Return the updated board -- assumes the game exists
+    /// Make a move in a game on behalf of the holder of the given seat token. Only the
+    ///  player whose turn it is may move or resign -- assumes the game exists
     pub async fn make_move(
         conn: &mut PgConnection,
         game_id: Uuid,
+        token: Option<Uuid>,
+        ply: Option<u16>,
         uci_move: &str,
         resign: bool,
     ) -> Result<(), GameError> {
+        // Hold the game until this transaction ends, so moves racing each other are played
+        //  one at a time, each on the board the last one left behind
+        Self::lock(conn, game_id).await?;
         let game = Self::latest(conn, game_id).await?;
 
-        // If the game is already over, just return it
-        if game.status == GameStatus::Complete {
-            return Err(GameError::GameComplete);
+        // Finished and abandoned games can't be played on
+        game.check_playable()?;
+
+        // Clients that say which ply they're moving from must have seen the latest board
+        let current_ply = game.board().moves_played();
+        if let Some(ply) = ply.filter(|ply| *ply != current_ply) {
+            return Err(GameError::StaleMove(ply, current_ply));
         }
 
         let mut board = game.board().clone();
         let player = board.turn();
+        Self::check_seat(conn, game_id, GamePlayer::from(player), token).await?;
+
+        // A player whose time has run out loses on time instead of moving. The loss stands
+        //  even though the move doesn't, so callers still commit on `TimedOut`.
+        let now = OffsetDateTime::now_utc();
+        let clock_ms = game.clock_ms(GamePlayer::from(player), now);
+        if clock_ms.is_some_and(|clock_ms| clock_ms <= 0) {
+            Self::time_out(conn, game_id, GamePlayer::from(player)).await?;
+            return Err(GameError::TimedOut(GamePlayer::from(player)));
+        }
 
-        // TODO: MAKE MORE CONCISE
         // If the current player is resigning, update the game status and return
         if resign {
             let game_winner = match player {
                 Player::White => GameWinner::Black,
                 Player::Black => GameWinner::White,
             };
-            let game_outcome = GameOutcome::Resignation;
-            let game_status = GameStatus::Complete;
+            return Self::complete(conn, game_id, game_winner, GameOutcome::Resignation).await;
+        }
+
+        let move_number = board.moves_played() as i32;
+
+        // Describe the move before it's made, since its SAN depends on the board it's played on
+        let bit_move = board
+            .check_move(uci_move)
+            .map_err(|e| GameError::InvalidMove(uci_move.to_string(), e))?;
+        let san = board.san(bit_move);
+        board.apply_move(bit_move);
+
+        Self::record_move(conn, game_id, move_number, &board, uci_move, &san).await?;
+
+        // Stop the mover's clock and start their opponent's
+        if let Some(time_control) = game.time_control {
+            let clock_ms = match game.clock_started_at {
+                Some(_) => clock_ms.map(|clock_ms| time_control.after_move_ms(clock_ms)),
+                None => clock_ms,
+            };
             sqlx::query!(
                 r#"UPDATE games
-                SET status = $1,
-                    winner = $2,
-                    outcome = $3
-                WHERE id = $4
+                SET white_clock_ms = CASE WHEN $2 = 'white' THEN $3 ELSE white_clock_ms END,
+                    black_clock_ms = CASE WHEN $2 = 'black' THEN $3 ELSE black_clock_ms END,
+                    clock_started_at = $4::TIMESTAMPTZ AT TIME ZONE 'UTC'
+                WHERE id = $1
                 "#,
-                game_status.to_string(),
-                game_winner.to_string(),
-                game_outcome.to_string(),
-                game_id
+                game_id,
+                GamePlayer::from(player).to_string(),
+                clock_ms,
+                now,
             )
             .execute(&mut *conn)
             .await?;
-            return Ok(());
         }
 
-        let move_number = board.moves_played() as i32;
+        // Moving instead of answering declines the opponent's draw offer, and lets any
+        //  takeback request lapse
+        sqlx::query!(
+            r#"UPDATE games
+            SET draw_offer = CASE WHEN draw_offer = $2 THEN NULL ELSE draw_offer END,
+                takeback_request = NULL
+            WHERE id = $1
+            "#,
+            game_id,
+            GamePlayer::from(player).opponent().to_string(),
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        // Check if the game is over
+        if board.checkmate() {
+            let game_winner = match player {
+                Player::White => GameWinner::White,
+                Player::Black => GameWinner::Black,
+            };
+            return Self::complete(conn, game_id, game_winner, GameOutcome::Checkmate).await;
+        } else if board.stalemate() {
+            return Self::complete(conn, game_id, GameWinner::Draw, GameOutcome::Stalemate).await;
+        } else if board.insufficient_material() {
+            return Self::complete(
+                conn,
+                game_id,
+                GameWinner::Draw,
+                GameOutcome::InsufficientMaterial,
+            )
+            .await;
+        } else if Self::repetitions(conn, game_id, &board).await? >= 3 {
+            return Self::complete(
+                conn,
+                game_id,
+                GameWinner::Draw,
+                GameOutcome::ThreefoldRepetition,
+            )
+            .await;
+        } else if board.rule_50() >= 100 {
+            return Self::complete(conn, game_id, GameWinner::Draw, GameOutcome::FiftyMoveRule)
+                .await;
+        }
+
+        // TODO: find a better way to do this -- maybe there will be an 'accept' game worflow in the future
+        // Update the game's status to active if it's not already
+        sqlx::query!(
+            r#"UPDATE games
+            SET status = 'active'
+            WHERE id = $1
+            AND status = 'created'
+            "#,
+            game_id,
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        // Return the updated board
+        Ok(())
+    }
 
-        // TODO: I don't like that this isn't an explicit error
-        // Attempt to make the move on the board
-        let success = board.apply_uci_move(uci_move);
-        if !success {
-            return Err(GameError::InvalidMove(uci_move.to_string()));
+    /// Return the ids of active games whose player to move had run out of time by `now`.
+    ///  Moves may be made before they're flagged, so check each with `flag_timeout`.
+    pub async fn timeout_candidates(
+        conn: &mut PgConnection,
+        now: OffsetDateTime,
+    ) -> Result<Vec<Uuid>, GameError> {
+        // White is to move after an even number of moves
+        let candidates = sqlx::query_scalar!(
+            r#"SELECT
+                g.id as "id: Uuid"
+            FROM games as g
+            WHERE g.status = 'active'
+            AND g.clock_started_at IS NOT NULL
+            AND g.clock_started_at + CASE
+                WHEN (SELECT COUNT(*) FROM moves as m WHERE m.game_id = g.id) % 2 = 0
+                THEN g.white_clock_ms
+                ELSE g.black_clock_ms
+            END * INTERVAL '1 millisecond' <= $1::TIMESTAMPTZ AT TIME ZONE 'UTC'
+            "#,
+            now,
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+        Ok(candidates)
+    }
+
+    /// Mark a game as lost on time if its player to move had run out of time by `now`,
+    ///  returning whether it was -- assumes the game exists
+    pub async fn flag_timeout(
+        conn: &mut PgConnection,
+        game_id: Uuid,
+        now: OffsetDateTime,
+    ) -> Result<bool, GameError> {
+        // A move may have been made since the game was picked out, so hold the game and
+        //  check its clock again before flagging it
+        Self::lock(conn, game_id).await?;
+        let game = Self::latest(conn, game_id).await?;
+        let player = GamePlayer::from(game.board.turn());
+        if game.check_playable().is_err()
+            || game
+                .clock_ms(player, now)
+                .is_none_or(|clock_ms| clock_ms > 0)
+        {
+            return Ok(false);
+        }
+        Self::time_out(conn, game_id, player).await?;
+        Ok(true)
+    }
+
+    /// Mark games that haven't been played on for the given number of seconds as
+    ///  abandoned, returning the ids of the games that were. Games on a running clock are
+    ///  left to run out of time instead.
+    pub async fn abandon_stale(
+        conn: &mut PgConnection,
+        idle_seconds: i64,
+    ) -> Result<Vec<Uuid>, GameError> {
+        let abandoned = sqlx::query_scalar!(
+            r#"UPDATE games
+            SET status = 'abandoned',
+                draw_offer = NULL,
+                takeback_request = NULL
+            WHERE status IN ('created', 'active')
+            AND clock_started_at IS NULL
+            AND updated_at < LOCALTIMESTAMP - $1 * INTERVAL '1 second'
+            RETURNING id as "id: Uuid"
+            "#,
+            idle_seconds as f64,
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+        Ok(abandoned)
+    }
+
+    /// Offer a draw on behalf of the holder of the given seat token, whoever's turn it is --
+    ///  assumes the game exists. A token holding both seats offers for the player who just
+    ///  moved.
+    pub async fn offer_draw(
+        conn: &mut PgConnection,
+        game_id: Uuid,
+        token: Option<Uuid>,
+    ) -> Result<(), GameError> {
+        Self::lock(conn, game_id).await?;
+        let game = Self::latest(conn, game_id).await?;
+
+        game.check_playable()?;
+        if game.draw_offer.is_some() {
+            return Err(GameError::DrawAlreadyOffered);
+        }
+
+        let player = Self::acting_seat(conn, &game, token).await?;
+        sqlx::query!(
+            r#"UPDATE games
+            SET draw_offer = $1
+            WHERE id = $2
+            "#,
+            player.to_string(),
+            game_id,
+        )
+        .execute(&mut *conn)
+        .await?;
+        Ok(())
+    }
+
+    /// Accept or decline the pending draw offer on behalf of the offering player's
+    ///  opponent -- assumes the game exists
+    pub async fn respond_to_draw(
+        conn: &mut PgConnection,
+        game_id: Uuid,
+        token: Option<Uuid>,
+        accept: bool,
+    ) -> Result<(), GameError> {
+        Self::lock(conn, game_id).await?;
+        let game = Self::latest(conn, game_id).await?;
+
+        game.check_playable()?;
+        let offered_by = match game.draw_offer {
+            Some(player) => player,
+            None => return Err(GameError::NoDrawOffer),
+        };
+        Self::check_seat(conn, game_id, offered_by.opponent(), token).await?;
+
+        if accept {
+            return Self::complete(conn, game_id, GameWinner::Draw, GameOutcome::Agreement).await;
+        }
+
+        sqlx::query!(
+            r#"UPDATE games
+            SET draw_offer = NULL
+            WHERE id = $1
+            "#,
+            game_id,
+        )
+        .execute(&mut *conn)
+        .await?;
+        Ok(())
+    }
+
+    /// Ask to take back a move on behalf of the holder of the given seat token -- assumes
+    ///  the game exists. A token holding both seats asks for the move just made. Bots
+    ///  don't mind, so in games against one the move is taken back straight away.
+    pub async fn request_takeback(
+        conn: &mut PgConnection,
+        game_id: Uuid,
+        token: Option<Uuid>,
+    ) -> Result<(), GameError> {
+        Self::lock(conn, game_id).await?;
+        let game = Self::latest(conn, game_id).await?;
+
+        game.check_takeback()?;
+        if game.takeback_request.is_some() {
+            return Err(GameError::TakebackAlreadyRequested);
+        }
+
+        let player = Self::acting_seat(conn, &game, token).await?;
+        if !game.has_move_to_take_back(player) {
+            return Err(GameError::NothingToTakeBack(player));
+        }
+
+        if game.bot_player == Some(player.opponent()) {
+            return Self::take_back(conn, &game, player).await;
+        }
+        sqlx::query!(
+            r#"UPDATE games
+            SET takeback_request = $1
+            WHERE id = $2
+            "#,
+            player.to_string(),
+            game_id,
+        )
+        .execute(&mut *conn)
+        .await?;
+        Ok(())
+    }
+
+    /// Accept or decline the pending takeback request on behalf of the requesting
+    ///  player's opponent -- assumes the game exists
+    pub async fn respond_to_takeback(
+        conn: &mut PgConnection,
+        game_id: Uuid,
+        token: Option<Uuid>,
+        accept: bool,
+    ) -> Result<(), GameError> {
+        Self::lock(conn, game_id).await?;
+        let game = Self::latest(conn, game_id).await?;
+
+        game.check_takeback()?;
+        let requested_by = match game.takeback_request {
+            Some(player) => player,
+            None => return Err(GameError::NoTakebackRequest),
+        };
+        Self::check_seat(conn, game_id, requested_by.opponent(), token).await?;
+
+        if accept {
+            return Self::take_back(conn, &game, requested_by).await;
+        }
+
+        sqlx::query!(
+            r#"UPDATE games
+            SET takeback_request = NULL
+            WHERE id = $1
+            "#,
+            game_id,
+        )
+        .execute(&mut *conn)
+        .await?;
+        Ok(())
+    }
+
+    /// Lock a game's row for the rest of the transaction
+    pub async fn lock(conn: &mut PgConnection, game_id: Uuid) -> Result<(), GameError> {
+        sqlx::query!(
+            r#"SELECT id
+            FROM games
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+            game_id,
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+        Ok(())
+    }
+
+    /// Pick the seat the holder of the given token is acting for, when it can be either
+    ///  player -- the player who just moved if the token holds both seats
+    async fn acting_seat(
+        conn: &mut PgConnection,
+        game: &Self,
+        token: Option<Uuid>,
+    ) -> Result<GamePlayer, GameError> {
+        let just_moved = GamePlayer::from(game.board().turn()).opponent();
+        let seats = match token {
+            Some(token) => Game::seats(conn, game.id, token).await?,
+            None => Vec::new(),
+        };
+        match seats.first() {
+            _ if seats.contains(&just_moved) => Ok(just_moved),
+            Some(player) => Ok(*player),
+            None => Err(GameError::WrongSeat(just_moved)),
+        }
+    }
+
+    /// Check that the given token holds a player's seat in a game
+    async fn check_seat(
+        conn: &mut PgConnection,
+        game_id: Uuid,
+        player: GamePlayer,
+        token: Option<Uuid>,
+    ) -> Result<(), GameError> {
+        let seated = match token {
+            Some(token) => Game::seats(conn, game_id, token).await?.contains(&player),
+            None => false,
+        };
+        if !seated {
+            return Err(GameError::WrongSeat(player));
+        }
+        Ok(())
+    }
+
+    /// Count how many times the given board's position has occurred in a game, including
+    ///  the starting position, which is never recorded in the moves table
+    async fn repetitions(
+        conn: &mut PgConnection,
+        game_id: Uuid,
+        board: &Board,
+    ) -> Result<usize, GameError> {
+        let history = sqlx::query_scalar!(
+            r#"SELECT
+                p.board as "board: Board"
+            FROM positions as p
+            JOIN moves as m ON m.position_id = p.id
+            WHERE m.game_id = $1
+            "#,
+            game_id,
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let key = board.repetition_key();
+        let repetitions = std::iter::once(Board::new())
+            .chain(history)
+            .filter(|position| position.repetition_key() == key)
+            .count();
+        Ok(repetitions)
+    }
+
+    /// An earlier board of this game, with the moves that led to it
+    fn rewound(&self, board: Board) -> Self {
+        Self {
+            id: self.id,
+            status: GameStatus::Active,
+            winner: None,
+            outcome: None,
+            draw_offer: None,
+            takeback_request: None,
+            white_seated: self.white_seated,
+            black_seated: self.black_seated,
+            white_player: self.white_player.clone(),
+            black_player: self.black_player.clone(),
+            bot: self.bot,
+            bot_player: self.bot_player,
+            moves: self
+                .moves
+                .iter()
+                .take(board.moves_played() as usize)
+                .cloned()
+                .collect(),
+            time_control: self.time_control,
+            white_clock_ms: None,
+            black_clock_ms: None,
+            clock_started_at: None,
+            board,
         }
+    }
 
+    /// Record the board reached by a game's move, sharing the position with any other game
+    ///  that has reached it
+    async fn record_move(
+        conn: &mut PgConnection,
+        game_id: Uuid,
+        move_number: i32,
+        board: &Board,
+        uci: &str,
+        san: &str,
+    ) -> Result<(), GameError> {
         // Insert the FEN into the database if it doesn't already exist
         // Return the position ID
         let board_fen = board.fen();
@@ -237,74 +1183,133 @@ impl GameBoard {
 
         // Insert the move into the database
         sqlx::query!(
-            r#"INSERT INTO moves (game_id, position_id, move_number)
-            VALUES ($1, $2, $3)
+            r#"INSERT INTO moves (game_id, position_id, move_number, uci, san)
+            VALUES ($1, $2, $3, $4, $5)
             "#,
             game_id,
             position_id,
             move_number,
+            uci,
+            san,
         )
         .execute(&mut *conn)
         .await?;
+        Ok(())
+    }
 
-        // Check if the game is over
-        if board.checkmate() {
-            let game_winner = match player {
-                Player::White => GameWinner::White,
-                Player::Black => GameWinner::Black,
-            };
-            let game_outcome = GameOutcome::Checkmate;
-            let game_status = GameStatus::Complete;
-            sqlx::query!(
-                r#"UPDATE games
-                SET status = $1,
-                    winner = $2,
-                    outcome = $3
-                WHERE id = $4
-                "#,
-                game_status.to_string(),
-                game_winner.to_string(),
-                game_outcome.to_string(),
-                game_id,
-            )
-            .execute(&mut *conn)
-            .await?;
-            return Ok(());
-        } else if board.stalemate() {
-            let game_winner = GameWinner::Draw;
-            let game_outcome = GameOutcome::Stalemate;
-            let game_status = GameStatus::Complete;
+    /// Undo a player's last move, and their opponent's reply if it's been made. The
+    ///  positions they reached stay, since other games may share them. Any result the
+    ///  board gave is undone with them, and the clock picks up again from the new board.
+    async fn take_back(
+        conn: &mut PgConnection,
+        game: &Self,
+        player: GamePlayer,
+    ) -> Result<(), GameError> {
+        let ply = (game.board.moves_played() - game.takeback_plies(player)) as i32;
+
+        // Moves are numbered by the ply they were played from
+        sqlx::query!(
+            r#"DELETE FROM moves
+            WHERE game_id = $1
+            AND move_number >= $2
+            "#,
+            game.id,
+            ply,
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        // The player to move is charged for the time they spent before the takeback, and
+        //  clocks only run once a move has been made
+        if game.time_control.is_some() {
+            let now = OffsetDateTime::now_utc();
+            let mover = GamePlayer::from(game.board.turn());
             sqlx::query!(
                 r#"UPDATE games
-                SET winner = $1,
-                    status = $2,
-                    outcome = $3
-                WHERE id = $4
+                SET white_clock_ms = CASE WHEN $2 = 'white' THEN $3 ELSE white_clock_ms END,
+                    black_clock_ms = CASE WHEN $2 = 'black' THEN $3 ELSE black_clock_ms END,
+                    clock_started_at = $4::TIMESTAMPTZ AT TIME ZONE 'UTC'
+                WHERE id = $1
                 "#,
-                game_winner.to_string(),
-                game_status.to_string(),
-                game_outcome.to_string(),
-                game_id,
+                game.id,
+                mover.to_string(),
+                game.clock_ms(mover, now),
+                (ply > 0).then_some(now),
             )
             .execute(&mut *conn)
             .await?;
-            return Ok(());
         }
 
-        // TODO: find a better way to do this -- maybe there will be an 'accept' game worflow in the future
-        // Update the game's status to active if it's not already
+        // The board before a game ended never ends it, or the game would've ended there
+        let status = match ply {
+            0 => GameStatus::Created,
+            _ => GameStatus::Active,
+        };
         sqlx::query!(
             r#"UPDATE games
-            SET status = 'active'
+            SET status = $1,
+                winner = NULL,
+                outcome = NULL,
+                draw_offer = NULL,
+                takeback_request = NULL
+            WHERE id = $2
+            "#,
+            status.to_string(),
+            game.id,
+        )
+        .execute(&mut *conn)
+        .await?;
+        Ok(())
+    }
+
+    /// End a game lost on time by the given player, leaving their clock empty
+    async fn time_out(
+        conn: &mut PgConnection,
+        game_id: Uuid,
+        player: GamePlayer,
+    ) -> Result<(), GameError> {
+        sqlx::query!(
+            r#"UPDATE games
+            SET white_clock_ms = CASE WHEN $2 = 'white' THEN 0 ELSE white_clock_ms END,
+                black_clock_ms = CASE WHEN $2 = 'black' THEN 0 ELSE black_clock_ms END
             WHERE id = $1
-            AND status = 'created'
             "#,
             game_id,
+            player.to_string(),
         )
         .execute(&mut *conn)
         .await?;
 
-        // Return the updated board
+        let game_winner = match player.opponent() {
+            GamePlayer::White => GameWinner::White,
+            GamePlayer::Black => GameWinner::Black,
+        };
+        Self::complete(conn, game_id, game_winner, GameOutcome::Timeout).await
+    }
+
+    /// Mark a game as complete with the given winner and outcome
+    async fn complete(
+        conn: &mut PgConnection,
+        game_id: Uuid,
+        game_winner: GameWinner,
+        game_outcome: GameOutcome,
+    ) -> Result<(), GameError> {
+        sqlx::query!(
+            r#"UPDATE games
+            SET status = $1,
+                winner = $2,
+                outcome = $3,
+                draw_offer = NULL,
+                takeback_request = NULL
+            WHERE id = $4
+            "#,
+            GameStatus::Complete.to_string(),
+            game_winner.to_string(),
+            game_outcome.to_string(),
+            game_id,
+        )
+        .execute(&mut *conn)
+        .await?;
         Ok(())
     }
 }
@@ -313,8 +1318,113 @@ impl GameBoard {
 pub enum GameError {
     #[error("sqlx error: {0}")]
     Sqlx(#[from] sqlx::Error),
-    #[error("invalid move: {0}")]
-    InvalidMove(String),
+    #[error("invalid move {0}: {1}")]
+    InvalidMove(String, MoveError),
+    #[error("game {0}, ply {1}: illegal move {2}")]
+    IllegalPgnMove(usize, usize, String),
+    #[error("game {0}: games starting from a custom position can't be imported")]
+    UnsupportedPgnStart(usize),
+    #[error("no notation was recorded for ply {0}")]
+    MissingNotation(usize),
+    #[error("move was made from ply {0}, but the game is at ply {1}")]
+    StaleMove(u16, u16),
+    #[error("{0} ran out of time before moving")]
+    TimedOut(GamePlayer),
     #[error("game already complete")]
     GameComplete,
+    #[error("game was abandoned")]
+    GameAbandoned,
+    #[error("a draw has already been offered")]
+    DrawAlreadyOffered,
+    #[error("no draw has been offered")]
+    NoDrawOffer,
+    #[error("a takeback has already been requested")]
+    TakebackAlreadyRequested,
+    #[error("no takeback has been requested")]
+    NoTakebackRequest,
+    #[error("{0} hasn't made a move to take back")]
+    NothingToTakeBack(GamePlayer),
+    #[error("only the {0} player can do that")]
+    WrongSeat(GamePlayer),
+    #[error("the {0} seat is already taken")]
+    SeatTaken(GamePlayer),
+    #[error(
+        "bot depth must be between {} and {}, got {0}",
+        MIN_BOT_DEPTH,
+        MAX_BOT_DEPTH
+    )]
+    InvalidBotDepth(i16),
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+
+    #[sqlx::test]
+    async fn repeating_a_position_three_times_draws(pool: PgPool) {
+        let mut conn = pool.acquire().await.unwrap();
+        let game = NewGame::create(&mut conn, None).await.unwrap();
+        let game_id = game.id();
+        let token = Game::claim_seat(&mut conn, game_id, GamePlayer::White, None, None)
+            .await
+            .unwrap();
+        Game::claim_seat(&mut conn, game_id, GamePlayer::Black, Some(token), None)
+            .await
+            .unwrap();
+
+        // The starting position comes up for the third time with the last move
+        let shuffle = [
+            "g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8",
+        ];
+        for (ply, uci_move) in shuffle.iter().enumerate() {
+            let game_board = GameBoard::latest(&mut conn, game_id).await.unwrap();
+            assert_ne!(
+                *game_board.status(),
+                GameStatus::Complete,
+                "drawn at ply {}",
+                ply
+            );
+            GameBoard::make_move(&mut conn, game_id, Some(token), None, uci_move, false)
+                .await
+                .unwrap();
+        }
+
+        let game_board = GameBoard::latest(&mut conn, game_id).await.unwrap();
+        assert_eq!(*game_board.status(), GameStatus::Complete);
+        assert_eq!(*game_board.winner(), Some(GameWinner::Draw));
+        assert_eq!(
+            *game_board.outcome(),
+            Some(GameOutcome::ThreefoldRepetition)
+        );
+    }
+
+    #[sqlx::test]
+    async fn importing_reports_illegal_moves_by_game_and_ply(pool: PgPool) {
+        let mut conn = pool.acquire().await.unwrap();
+        let pgn_games = PgnGame::parse_all("1. e4 e5 1-0\n\n1. e4 {fine} e5 2. Ke3 *").unwrap();
+
+        let game = NewGame::import(&mut conn, 1, &pgn_games[0]).await.unwrap();
+        assert_eq!(*game.winner(), Some(GameWinner::White));
+        assert_eq!(*game.outcome(), Some(GameOutcome::Resignation));
+
+        let error = NewGame::import(&mut conn, 2, &pgn_games[1]).await;
+        assert!(matches!(
+            error,
+            Err(GameError::IllegalPgnMove(2, 3, ref san)) if san == "Ke3"
+        ));
+    }
+
+    #[sqlx::test]
+    async fn importing_names_the_draw_rule_that_forced_a_draw(pool: PgPool) {
+        let mut conn = pool.acquire().await.unwrap();
+        let pgn = "1. Nf3 Nf6 2. Ng1 Ng8 3. Nf3 Nf6 4. Ng1 Ng8 1/2-1/2\n\n1. e4 e5 1/2-1/2";
+        let pgn_games = PgnGame::parse_all(pgn).unwrap();
+
+        let repeated = NewGame::import(&mut conn, 1, &pgn_games[0]).await.unwrap();
+        assert_eq!(*repeated.outcome(), Some(GameOutcome::ThreefoldRepetition));
+        let agreed = NewGame::import(&mut conn, 2, &pgn_games[1]).await.unwrap();
+        assert_eq!(*agreed.outcome(), Some(GameOutcome::Agreement));
+    }
 }
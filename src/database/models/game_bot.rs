@@ -0,0 +1,104 @@
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+
+use pleco::bots::{AlphaBetaSearcher, IterativeSearcher, JamboreeSearcher, MiniMaxSearcher};
+use pleco::tools::Searcher;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Uuid;
+use sqlx::PgConnection;
+
+use super::game_player::GamePlayer;
+
+use crate::database::types::DatabaseBoard as Board;
+
+/// Shallowest and deepest searches a bot can be asked to run
+pub const MIN_BOT_DEPTH: i16 = 1;
+pub const MAX_BOT_DEPTH: i16 = 4;
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum GameBot {
+    Minimax,
+    AlphaBeta,
+    Jamboree,
+    IterativeParallelMvv,
+}
+
+impl GameBot {
+    /// Search for the bot's best move on a board, returned in UCI format. This is CPU
+    ///  heavy, so keep it off the async runtime.
+    pub fn best_move(&self, board: &Board, depth: i16) -> String {
+        let board = (**board).clone();
+        let depth = depth.clamp(MIN_BOT_DEPTH, MAX_BOT_DEPTH) as u16;
+        let bit_move = match self {
+            GameBot::Minimax => MiniMaxSearcher::best_move(board, depth),
+            GameBot::AlphaBeta => AlphaBetaSearcher::best_move(board, depth),
+            GameBot::Jamboree => JamboreeSearcher::best_move(board, depth),
+            GameBot::IterativeParallelMvv => IterativeSearcher::best_move(board, depth),
+        };
+        bit_move.stringify()
+    }
+}
+
+impl Display for GameBot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            GameBot::Minimax => write!(f, "minimax"),
+            GameBot::AlphaBeta => write!(f, "alpha_beta"),
+            GameBot::Jamboree => write!(f, "jamboree"),
+            GameBot::IterativeParallelMvv => write!(f, "iterative_parallel_mvv"),
+        }
+    }
+}
+
+impl TryFrom<&str> for GameBot {
+    type Error = GameBotError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "minimax" => Ok(GameBot::Minimax),
+            "alpha_beta" => Ok(GameBot::AlphaBeta),
+            "jamboree" => Ok(GameBot::Jamboree),
+            "iterative_parallel_mvv" => Ok(GameBot::IterativeParallelMvv),
+            _ => Err(GameBotError::InvalidGameBot),
+        }
+    }
+}
+
+/// A bot seated in a game, along with the seat token it plays through
+#[derive(Debug, Clone)]
+pub struct BotSeat {
+    pub bot: GameBot,
+    pub depth: i16,
+    pub player: GamePlayer,
+    pub token: Uuid,
+}
+
+impl BotSeat {
+    /// Read the bot seated in a game, if it's being played against the computer
+    pub async fn read(conn: &mut PgConnection, game_id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        let bot_seat = sqlx::query_as!(
+            Self,
+            r#"SELECT
+                bot as "bot!: GameBot",
+                bot_depth as "depth!",
+                bot_player as "player!: GamePlayer",
+                CASE WHEN bot_player = 'white' THEN white_token ELSE black_token END as "token!: Uuid"
+            FROM games
+            WHERE id = $1
+            AND bot IS NOT NULL
+            "#,
+            game_id,
+        )
+        .fetch_optional(&mut *conn)
+        .await?;
+        Ok(bot_seat)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum GameBotError {
+    #[error("Invalid GameBot")]
+    InvalidGameBot,
+}
@@ -0,0 +1,129 @@
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+
+use serde::Deserialize;
+use sqlx::types::Uuid;
+use time::OffsetDateTime;
+
+use super::game_outcome::GameOutcome;
+use super::game_status::GameStatus;
+use super::game_winner::GameWinner;
+
+/// Smallest and largest number of games in a page
+pub const MIN_PAGE_SIZE: i64 = 1;
+pub const MAX_PAGE_SIZE: i64 = 100;
+pub const DEFAULT_PAGE_SIZE: i64 = 20;
+
+/// Which games to list, in what order, and from where
+#[derive(Debug)]
+pub struct GameListQuery {
+    pub order: GameOrder,
+    pub direction: GameDirection,
+    pub status: Option<GameStatus>,
+    pub winner: Option<GameWinner>,
+    pub outcome: Option<GameOutcome>,
+    pub after: Option<GameCursor>,
+    pub limit: i64,
+}
+
+impl Default for GameListQuery {
+    fn default() -> Self {
+        Self {
+            order: GameOrder::default(),
+            direction: GameDirection::default(),
+            status: None,
+            winner: None,
+            outcome: None,
+            after: None,
+            limit: DEFAULT_PAGE_SIZE,
+        }
+    }
+}
+
+/// Which time to order a list of games by
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GameOrder {
+    /// When the game was created
+    #[default]
+    Created,
+    /// When the game was last played on
+    Activity,
+}
+
+impl Display for GameOrder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            GameOrder::Created => write!(f, "created"),
+            GameOrder::Activity => write!(f, "activity"),
+        }
+    }
+}
+
+/// Whether a list of games starts from the latest or earliest time
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GameDirection {
+    #[default]
+    Newest,
+    Oldest,
+}
+
+impl Display for GameDirection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            GameDirection::Newest => write!(f, "newest"),
+            GameDirection::Oldest => write!(f, "oldest"),
+        }
+    }
+}
+
+/// Where a page of games left off: the last game's sort time, in microseconds since the
+///  epoch, and its id. Written like `1708719317123456_<uuid>`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GameCursor {
+    micros: i64,
+    id: Uuid,
+}
+
+impl GameCursor {
+    pub fn new(at: OffsetDateTime, id: Uuid) -> Self {
+        Self {
+            micros: (at.unix_timestamp_nanos() / 1_000) as i64,
+            id,
+        }
+    }
+
+    pub fn micros(&self) -> i64 {
+        self.micros
+    }
+
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+}
+
+impl Display for GameCursor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}_{}", self.micros, self.id)
+    }
+}
+
+impl TryFrom<&str> for GameCursor {
+    type Error = GameCursorError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let invalid = || GameCursorError::InvalidCursor(value.to_string());
+        let (micros, id) = value.split_once('_').ok_or_else(invalid)?;
+        Ok(Self {
+            micros: micros.parse().map_err(|_| invalid())?,
+            id: id.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum GameCursorError {
+    #[error("invalid cursor: {0}")]
+    InvalidCursor(String),
+}
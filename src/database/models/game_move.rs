@@ -0,0 +1,81 @@
+use sqlx::types::Uuid;
+use sqlx::PgConnection;
+
+use crate::database::types::DatabaseBoard as Board;
+
+/// The moves played in games, recorded in both UCI and SAN
+pub struct GameMove;
+
+impl GameMove {
+    /// Fill in the notation of moves recorded before it was stored, by finding the move
+    ///  between each pair of consecutive positions. Moves with no legal move leading to
+    ///  them are marked, so they aren't looked at again. Returns how many moves were
+    ///  filled in.
+    pub async fn backfill(conn: &mut PgConnection) -> Result<usize, sqlx::Error> {
+        let moves = sqlx::query!(
+            r#"SELECT
+                m.id as "id: Uuid",
+                m.game_id as "game_id: Uuid",
+                m.uci,
+                m.notation_underivable,
+                p.board as "board: Board"
+            FROM moves as m
+            JOIN positions as p ON p.id = m.position_id
+            WHERE m.game_id IN (
+                SELECT game_id FROM moves WHERE uci IS NULL AND NOT notation_underivable
+            )
+            ORDER BY m.game_id, m.move_number ASC
+            "#,
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut filled = 0;
+        let mut game_id = None;
+        let mut previous = Board::new();
+        for row in moves {
+            // Each game starts over from the standard position
+            if game_id != Some(row.game_id) {
+                game_id = Some(row.game_id);
+                previous = Board::new();
+            }
+
+            if row.uci.is_none() && !row.notation_underivable {
+                match previous.move_to(&row.board) {
+                    Some(bit_move) => {
+                        sqlx::query!(
+                            r#"UPDATE moves
+                            SET uci = $1, san = $2
+                            WHERE id = $3
+                            "#,
+                            bit_move.stringify(),
+                            previous.san(bit_move),
+                            row.id,
+                        )
+                        .execute(&mut *conn)
+                        .await?;
+                        filled += 1;
+                    }
+                    None => {
+                        tracing::warn!(
+                            "no legal move between positions: game_id={} | move_id={}",
+                            row.game_id,
+                            row.id
+                        );
+                        sqlx::query!(
+                            r#"UPDATE moves
+                            SET notation_underivable = TRUE
+                            WHERE id = $1
+                            "#,
+                            row.id,
+                        )
+                        .execute(&mut *conn)
+                        .await?;
+                    }
+                }
+            }
+            previous = row.board;
+        }
+        Ok(filled)
+    }
+}
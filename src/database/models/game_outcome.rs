@@ -4,11 +4,17 @@ use std::fmt::{self, Display, Formatter};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
 #[sqlx(rename_all = "snake_case")]
 pub enum GameOutcome {
     Checkmate,
     Stalemate,
     Resignation,
+    ThreefoldRepetition,
+    FiftyMoveRule,
+    InsufficientMaterial,
+    Agreement,
+    Timeout,
 }
 
 impl Display for GameOutcome {
@@ -17,6 +23,25 @@ impl Display for GameOutcome {
             GameOutcome::Checkmate => write!(f, "checkmate"),
             GameOutcome::Stalemate => write!(f, "stalemate"),
             GameOutcome::Resignation => write!(f, "resignation"),
+            GameOutcome::ThreefoldRepetition => write!(f, "threefold_repetition"),
+            GameOutcome::FiftyMoveRule => write!(f, "fifty_move_rule"),
+            GameOutcome::InsufficientMaterial => write!(f, "insufficient_material"),
+            GameOutcome::Agreement => write!(f, "agreement"),
+            GameOutcome::Timeout => write!(f, "timeout"),
+        }
+    }
+}
+
+impl GameOutcome {
+    /// Whether the board itself ended the game, rather than a player or the clock
+    pub fn decided_on_board(&self) -> bool {
+        match self {
+            GameOutcome::Checkmate
+            | GameOutcome::Stalemate
+            | GameOutcome::ThreefoldRepetition
+            | GameOutcome::FiftyMoveRule
+            | GameOutcome::InsufficientMaterial => true,
+            GameOutcome::Resignation | GameOutcome::Agreement | GameOutcome::Timeout => false,
         }
     }
 }
@@ -29,6 +54,11 @@ impl TryFrom<&str> for GameOutcome {
             "checkmate" => Ok(GameOutcome::Checkmate),
             "stalemate" => Ok(GameOutcome::Stalemate),
             "resignation" => Ok(GameOutcome::Resignation),
+            "threefold_repetition" => Ok(GameOutcome::ThreefoldRepetition),
+            "fifty_move_rule" => Ok(GameOutcome::FiftyMoveRule),
+            "insufficient_material" => Ok(GameOutcome::InsufficientMaterial),
+            "agreement" => Ok(GameOutcome::Agreement),
+            "timeout" => Ok(GameOutcome::Timeout),
             _ => Err(GameOutcomeError::InvalidGameOutcome),
         }
     }
@@ -0,0 +1,260 @@
+/// Tokens that end a game's movetext
+const RESULT_TOKENS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+
+/// A game read from a PGN file: its tag pairs, its moves in SAN, and its result token
+#[derive(Debug, Default)]
+pub struct PgnGame {
+    tags: Vec<(String, String)>,
+    moves: Vec<String>,
+    result: Option<String>,
+}
+
+impl PgnGame {
+    pub fn moves(&self) -> &[String] {
+        &self.moves
+    }
+
+    /// The game's result token, `*` if the file didn't give one
+    pub fn result(&self) -> &str {
+        self.result.as_deref().unwrap_or("*")
+    }
+
+    /// Look up the value of a tag pair, e.g. `White`
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.moves.is_empty() && self.result.is_none()
+    }
+
+    /// Parse every game in a PGN file. Comments, variations and annotations are skipped,
+    ///  only the main line is kept.
+    pub fn parse_all(pgn: &str) -> Result<Vec<PgnGame>, PgnError> {
+        let mut games = Vec::new();
+        let mut game = PgnGame::default();
+        let mut chars = pgn.trim_start_matches('\u{feff}').chars().peekable();
+        let mut line_start = true;
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\n' => {
+                    line_start = true;
+                    continue;
+                }
+                c if c.is_whitespace() => continue,
+                // Escaped lines are ignored
+                '%' if line_start => {
+                    for c in chars.by_ref() {
+                        if c == '\n' {
+                            break;
+                        }
+                    }
+                }
+                ';' => {
+                    for c in chars.by_ref() {
+                        if c == '\n' {
+                            break;
+                        }
+                    }
+                }
+                '{' => {
+                    if !chars.by_ref().any(|c| c == '}') {
+                        return Err(PgnError::Unterminated("comment"));
+                    }
+                }
+                '(' => {
+                    let mut depth = 1;
+                    for c in chars.by_ref() {
+                        match c {
+                            '(' => depth += 1,
+                            ')' => depth -= 1,
+                            _ => {}
+                        }
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                    if depth != 0 {
+                        return Err(PgnError::Unterminated("variation"));
+                    }
+                }
+                '[' => {
+                    // Tags after movetext start the next game, even if the last one
+                    //  was missing its result
+                    if !game.moves.is_empty() {
+                        games.push(std::mem::take(&mut game));
+                    }
+                    let mut tag_pair = String::new();
+                    let mut in_string = false;
+                    let mut escaped = false;
+                    let mut closed = false;
+                    for c in chars.by_ref() {
+                        if !in_string && c == ']' {
+                            closed = true;
+                            break;
+                        }
+                        if in_string && !escaped && c == '\\' {
+                            escaped = true;
+                            continue;
+                        }
+                        if !escaped && c == '"' {
+                            in_string = !in_string;
+                        }
+                        escaped = false;
+                        tag_pair.push(c);
+                    }
+                    if !closed {
+                        return Err(PgnError::Unterminated("tag pair"));
+                    }
+                    game.tags.push(parse_tag_pair(&tag_pair)?);
+                }
+                _ => {
+                    let mut symbol = c.to_string();
+                    while let Some(c) = chars.peek() {
+                        if c.is_whitespace() || "{}();[]".contains(*c) {
+                            break;
+                        }
+                        symbol.push(*c);
+                        chars.next();
+                    }
+
+                    if RESULT_TOKENS.contains(&symbol.as_str()) {
+                        game.result = Some(symbol);
+                        games.push(std::mem::take(&mut game));
+                    } else if !symbol.starts_with('$') {
+                        // Drop move numbers like `12.` or `12...`, which may run into the move
+                        let san = match symbol.rfind('.') {
+                            Some(index) => &symbol[index + 1..],
+                            None => &symbol,
+                        };
+                        if !san.is_empty() {
+                            game.moves.push(san.to_string());
+                        }
+                    }
+                }
+            }
+            line_start = false;
+        }
+
+        if !game.is_empty() {
+            games.push(game);
+        }
+        if games.is_empty() {
+            return Err(PgnError::NoGames);
+        }
+        Ok(games)
+    }
+}
+
+/// Split the inside of a tag pair, e.g. `White "Morphy, Paul"`, into its name and value
+fn parse_tag_pair(tag_pair: &str) -> Result<(String, String), PgnError> {
+    let tag_pair = tag_pair.trim();
+    let (name, value) = tag_pair
+        .split_once(char::is_whitespace)
+        .ok_or_else(|| PgnError::InvalidTag(tag_pair.to_string()))?;
+    let value = value
+        .trim()
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .ok_or_else(|| PgnError::InvalidTag(tag_pair.to_string()))?;
+    Ok((name.to_string(), value.to_string()))
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PgnError {
+    #[error("no games found")]
+    NoGames,
+    #[error("unterminated {0}")]
+    Unterminated(&'static str),
+    #[error("invalid tag pair: [{0}]")]
+    InvalidTag(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_one(pgn: &str) -> PgnGame {
+        let mut games = PgnGame::parse_all(pgn).unwrap();
+        assert_eq!(games.len(), 1);
+        games.remove(0)
+    }
+
+    #[test]
+    fn reads_tags_moves_and_result() {
+        let game = parse_one(
+            "[Event \"Casual\"]\n[White \"Morphy, Paul\"]\n[Black \"?\"]\n\n1. e4 e5 2. Nf3 1-0\n",
+        );
+        assert_eq!(game.tag("Event"), Some("Casual"));
+        assert_eq!(game.tag("White"), Some("Morphy, Paul"));
+        assert_eq!(game.tag("Black"), Some("?"));
+        assert_eq!(game.tag("Site"), None);
+        assert_eq!(game.moves(), ["e4", "e5", "Nf3"]);
+        assert_eq!(game.result(), "1-0");
+    }
+
+    #[test]
+    fn unescapes_tag_values() {
+        let game = parse_one(r#"[White "Paul \"The Pride\" Morphy \\ NOLA"] *"#);
+        assert_eq!(game.tag("White"), Some(r#"Paul "The Pride" Morphy \ NOLA"#));
+    }
+
+    #[test]
+    fn skips_comments() {
+        let game = parse_one("1. e4 {best by test} e5 ; a line comment 2. Nf4\n2. Nf3 *");
+        assert_eq!(game.moves(), ["e4", "e5", "Nf3"]);
+    }
+
+    #[test]
+    fn skips_variations_even_nested() {
+        let game = parse_one("1. e4 (1. d4 d5 (1... Nf6 2. c4)) 1... e5 (1... c5) 2. Nf3 *");
+        assert_eq!(game.moves(), ["e4", "e5", "Nf3"]);
+    }
+
+    #[test]
+    fn skips_nags_and_move_numbers() {
+        let game = parse_one("1.e4 $1 1...e5 $2 $14 2.Nf3!? *");
+        assert_eq!(game.moves(), ["e4", "e5", "Nf3!?"]);
+    }
+
+    #[test]
+    fn skips_escaped_lines() {
+        let game = parse_one("% exported by something\n1. e4 e5 *");
+        assert_eq!(game.moves(), ["e4", "e5"]);
+    }
+
+    #[test]
+    fn splits_games() {
+        let games = PgnGame::parse_all("[White \"a\"]\n1. e4 1-0\n\n[White \"b\"]\n1. d4").unwrap();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].tag("White"), Some("a"));
+        assert_eq!(games[1].tag("White"), Some("b"));
+        assert_eq!(games[1].moves(), ["d4"]);
+        assert_eq!(games[1].result(), "*");
+    }
+
+    #[test]
+    fn rejects_malformed_files() {
+        assert!(matches!(PgnGame::parse_all("  \n"), Err(PgnError::NoGames)));
+        assert!(matches!(
+            PgnGame::parse_all("1. e4 {unfinished"),
+            Err(PgnError::Unterminated("comment"))
+        ));
+        assert!(matches!(
+            PgnGame::parse_all("1. e4 (1. d4"),
+            Err(PgnError::Unterminated("variation"))
+        ));
+        assert!(matches!(
+            PgnGame::parse_all("[White \"a\""),
+            Err(PgnError::Unterminated("tag pair"))
+        ));
+        assert!(matches!(
+            PgnGame::parse_all("[White a] *"),
+            Err(PgnError::InvalidTag(_))
+        ));
+    }
+}
@@ -0,0 +1,58 @@
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+
+use pleco::core::Player;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum GamePlayer {
+    White,
+    Black,
+}
+
+impl GamePlayer {
+    pub fn opponent(&self) -> Self {
+        match self {
+            GamePlayer::White => GamePlayer::Black,
+            GamePlayer::Black => GamePlayer::White,
+        }
+    }
+}
+
+impl Display for GamePlayer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            GamePlayer::White => write!(f, "white"),
+            GamePlayer::Black => write!(f, "black"),
+        }
+    }
+}
+
+impl From<Player> for GamePlayer {
+    fn from(player: Player) -> Self {
+        match player {
+            Player::White => GamePlayer::White,
+            Player::Black => GamePlayer::Black,
+        }
+    }
+}
+
+impl TryFrom<&str> for GamePlayer {
+    type Error = GamePlayerError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "white" => Ok(GamePlayer::White),
+            "black" => Ok(GamePlayer::Black),
+            _ => Err(GamePlayerError::InvalidGamePlayer),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum GamePlayerError {
+    #[error("Invalid GamePlayer")]
+    InvalidGamePlayer,
+}
@@ -1,9 +1,30 @@
 mod game;
+mod game_bot;
+mod game_list;
+mod game_move;
 mod game_outcome;
+mod game_pgn;
+mod game_player;
 mod game_status;
 mod game_winner;
+mod move_request;
+mod session;
+mod time_control;
+mod user;
 
 pub use game::{Game, GameBoard, GameError, NewGame};
+pub use game_bot::{BotSeat, GameBot, MAX_BOT_DEPTH, MIN_BOT_DEPTH};
+pub use game_list::{
+    GameCursor, GameDirection, GameListQuery, GameOrder, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE,
+    MIN_PAGE_SIZE,
+};
+pub use game_move::GameMove;
 pub use game_outcome::GameOutcome;
+pub use game_pgn::{PgnError, PgnGame};
+pub use game_player::GamePlayer;
 pub use game_status::GameStatus;
 pub use game_winner::GameWinner;
+pub use move_request::{MoveRequest, MAX_IDEMPOTENCY_KEY_LENGTH};
+pub use session::{Session, SESSION_LENGTH_DAYS};
+pub use time_control::TimeControl;
+pub use user::{NewUser, User, UserError};
@@ -0,0 +1,88 @@
+use sqlx::types::Uuid;
+use sqlx::PgConnection;
+
+/// Longest idempotency key a client can send
+pub const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 255;
+
+/// A move request that was made with an idempotency key, and where it left the game
+pub struct MoveRequest {
+    uci_move: String,
+    resign: bool,
+    ply: i32,
+}
+
+impl MoveRequest {
+    /// The ply the game was at once the request was handled
+    pub fn ply(&self) -> i32 {
+        self.ply
+    }
+
+    /// Whether another request asks for the same thing as this one. Resignations are the
+    ///  same whatever move came with them.
+    pub fn matches(&self, uci_move: &str, resign: bool) -> bool {
+        self.resign == resign && (resign || self.uci_move == uci_move)
+    }
+
+    /// Look up the request a game was sent with the given key, if there was one
+    pub async fn find(
+        conn: &mut PgConnection,
+        game_id: Uuid,
+        idempotency_key: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let move_request = sqlx::query_as!(
+            Self,
+            r#"SELECT
+                uci_move,
+                resign,
+                ply
+            FROM move_requests
+            WHERE game_id = $1
+            AND idempotency_key = $2
+            "#,
+            game_id,
+            idempotency_key,
+        )
+        .fetch_optional(&mut *conn)
+        .await?;
+        Ok(move_request)
+    }
+
+    /// Remember a request that was handled, along with the ply it left the game at. Only
+    ///  played moves are kept, since a resignation's move is never looked at.
+    pub async fn record(
+        conn: &mut PgConnection,
+        game_id: Uuid,
+        idempotency_key: &str,
+        uci_move: &str,
+        resign: bool,
+        ply: i32,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT INTO move_requests (game_id, idempotency_key, uci_move, resign, ply)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            game_id,
+            idempotency_key,
+            if resign { "" } else { uci_move },
+            resign,
+            ply,
+        )
+        .execute(&mut *conn)
+        .await?;
+        Ok(())
+    }
+
+    /// Forget requests made more than the given number of seconds ago, returning how many
+    ///  were forgotten
+    pub async fn expire(conn: &mut PgConnection, ttl_seconds: i64) -> Result<u64, sqlx::Error> {
+        let expired = sqlx::query!(
+            r#"DELETE FROM move_requests
+            WHERE created_at < LOCALTIMESTAMP - $1 * INTERVAL '1 second'
+            "#,
+            ttl_seconds as f64,
+        )
+        .execute(&mut *conn)
+        .await?;
+        Ok(expired.rows_affected())
+    }
+}
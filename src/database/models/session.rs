@@ -0,0 +1,40 @@
+use sqlx::types::Uuid;
+use sqlx::PgConnection;
+
+/// How long a login session lasts before the user has to log in again
+pub const SESSION_LENGTH_DAYS: i64 = 30;
+
+pub struct Session;
+
+impl Session {
+    /// Start a new login session for a user, returning the session id
+    pub async fn create(conn: &mut PgConnection, user_id: Uuid) -> Result<Uuid, sqlx::Error> {
+        let session_id = sqlx::query_scalar!(
+            r#"INSERT INTO sessions (user_id, expires_at)
+            VALUES ($1, CURRENT_TIMESTAMP + $2 * INTERVAL '1 day')
+            RETURNING id as "id: Uuid"
+            "#,
+            user_id,
+            SESSION_LENGTH_DAYS as f64,
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+        Ok(session_id)
+    }
+
+    /// End a login session
+    pub async fn delete(conn: &mut PgConnection, session_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(r#"DELETE FROM sessions WHERE id = $1"#, session_id)
+            .execute(&mut *conn)
+            .await?;
+        Ok(())
+    }
+
+    /// Delete every session that has expired, returning how many there were
+    pub async fn expire(conn: &mut PgConnection) -> Result<u64, sqlx::Error> {
+        let expired = sqlx::query!(r#"DELETE FROM sessions WHERE expires_at <= CURRENT_TIMESTAMP"#)
+            .execute(&mut *conn)
+            .await?;
+        Ok(expired.rows_affected())
+    }
+}
@@ -0,0 +1,112 @@
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+
+use serde::{Serialize, Serializer};
+use sqlx::error::BoxDynError;
+use sqlx::postgres::{PgTypeInfo, PgValueRef};
+use sqlx::{Decode, Postgres, Type};
+
+const MS_PER_SECOND: i64 = 1000;
+const MS_PER_MINUTE: i64 = 60 * MS_PER_SECOND;
+const MS_PER_DAY: i64 = 24 * 60 * MS_PER_MINUTE;
+
+/// Longest game, increment, and correspondence move allowed
+const MAX_MINUTES: i64 = 180;
+const MAX_SECONDS: i64 = 180;
+const MAX_DAYS: i64 = 14;
+
+/// How much time each side gets, written like `5+3` or `3d`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TimeControl {
+    /// Minutes for the whole game, plus seconds added after every move
+    Increment { minutes: i64, seconds: i64 },
+    /// Days for every move, with the clock reset after each one
+    Correspondence { days: i64 },
+}
+
+impl TimeControl {
+    /// The time each side starts the game with
+    pub fn initial_ms(&self) -> i64 {
+        match self {
+            TimeControl::Increment { minutes, .. } => minutes * MS_PER_MINUTE,
+            TimeControl::Correspondence { days } => days * MS_PER_DAY,
+        }
+    }
+
+    /// A player's remaining time after they move, given how long they had left when their
+    ///  clock was stopped
+    pub fn after_move_ms(&self, remaining_ms: i64) -> i64 {
+        match self {
+            TimeControl::Increment { seconds, .. } => remaining_ms + seconds * MS_PER_SECOND,
+            TimeControl::Correspondence { .. } => self.initial_ms(),
+        }
+    }
+}
+
+impl Display for TimeControl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeControl::Increment { minutes, seconds } => write!(f, "{}+{}", minutes, seconds),
+            TimeControl::Correspondence { days } => write!(f, "{}d", days),
+        }
+    }
+}
+
+impl TryFrom<&str> for TimeControl {
+    type Error = TimeControlError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let invalid = || TimeControlError::InvalidTimeControl(value.to_string());
+        let parse = |number: &str| number.parse::<i64>().map_err(|_| invalid());
+
+        let time_control = match (value.split_once('+'), value.strip_suffix('d')) {
+            (Some((minutes, seconds)), None) => TimeControl::Increment {
+                minutes: parse(minutes)?,
+                seconds: parse(seconds)?,
+            },
+            (None, Some(days)) => TimeControl::Correspondence { days: parse(days)? },
+            _ => return Err(invalid()),
+        };
+
+        // A game needs some time on the clock to be playable, but not forever
+        let in_range = match time_control {
+            TimeControl::Increment { minutes, seconds } => {
+                (0..=MAX_MINUTES).contains(&minutes) && (0..=MAX_SECONDS).contains(&seconds)
+            }
+            TimeControl::Correspondence { days } => (0..=MAX_DAYS).contains(&days),
+        };
+        if !in_range || time_control.initial_ms() <= 0 {
+            return Err(invalid());
+        }
+        Ok(time_control)
+    }
+}
+
+impl Decode<'_, Postgres> for TimeControl {
+    fn decode(value: PgValueRef<'_>) -> Result<Self, BoxDynError> {
+        let time_control = <String as Decode<Postgres>>::decode(value)?;
+        Ok(TimeControl::try_from(time_control.as_str())?)
+    }
+}
+
+impl Serialize for TimeControl {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl Type<Postgres> for TimeControl {
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <String as Type<Postgres>>::compatible(ty)
+    }
+
+    fn type_info() -> PgTypeInfo {
+        <String as Type<Postgres>>::type_info()
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TimeControlError {
+    #[error("invalid time control: {0}")]
+    InvalidTimeControl(String),
+}
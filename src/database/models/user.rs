@@ -0,0 +1,179 @@
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use sqlx::types::Uuid;
+use sqlx::FromRow;
+use sqlx::PgConnection;
+use time::OffsetDateTime;
+
+const MIN_USERNAME_LENGTH: usize = 3;
+const MAX_USERNAME_LENGTH: usize = 32;
+const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// A hash of a password nobody has, checked against when a login names an unknown user so
+///  a miss takes as long as a wrong password
+const DUMMY_PASSWORD_HASH: &str =
+    "$argon2id$v=19$m=19456,t=2,p=1$cYZ/F/sRrkivAQUl7O2Lcg$fLQl29fpfltptF9kQui8V6A2CygKMUPq5AqfV/yjM1Y";
+
+/// A user ready to be registered, with their password already hashed
+pub struct NewUser {
+    username: String,
+    password_hash: String,
+}
+
+impl NewUser {
+    /// Check a new user's username and password, and hash the password
+    pub async fn new(username: &str, password: &str) -> Result<Self, UserError> {
+        let username_length = username.chars().count();
+        if !(MIN_USERNAME_LENGTH..=MAX_USERNAME_LENGTH).contains(&username_length)
+            || !username
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(UserError::InvalidUsername);
+        }
+        if password.chars().count() < MIN_PASSWORD_LENGTH {
+            return Err(UserError::WeakPassword);
+        }
+
+        // Hashing is slow on purpose, so keep it off the async runtime
+        let password = password.to_string();
+        let password_hash = tokio::task::spawn_blocking(move || {
+            let salt = SaltString::generate(&mut OsRng);
+            Argon2::default()
+                .hash_password(password.as_bytes(), &salt)
+                .map(|password_hash| password_hash.to_string())
+        })
+        .await
+        .map_err(|_| UserError::PasswordHash)?
+        .map_err(|_| UserError::PasswordHash)?;
+
+        Ok(Self {
+            username: username.to_string(),
+            password_hash,
+        })
+    }
+
+    /// Register the new user
+    pub async fn create(self, conn: &mut PgConnection) -> Result<User, UserError> {
+        let maybe_user = sqlx::query_as!(
+            User,
+            r#"INSERT INTO users (username, password_hash)
+            VALUES ($1, $2)
+            ON CONFLICT (username) DO NOTHING
+            RETURNING
+                id as "id: Uuid",
+                username,
+                created_at as "created_at: OffsetDateTime"
+            "#,
+            self.username,
+            self.password_hash,
+        )
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        maybe_user.ok_or(UserError::UsernameTaken)
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, FromRow)]
+pub struct User {
+    id: Uuid,
+    username: String,
+    created_at: OffsetDateTime,
+}
+
+impl User {
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+
+    /// Read the user behind a login session, if the session exists and hasn't expired
+    pub async fn from_session(
+        conn: &mut PgConnection,
+        session_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let user = sqlx::query_as!(
+            Self,
+            r#"SELECT
+                u.id as "id: Uuid",
+                u.username,
+                u.created_at as "created_at: OffsetDateTime"
+            FROM sessions as s
+            JOIN users as u ON u.id = s.user_id
+            WHERE s.id = $1
+            AND s.expires_at > CURRENT_TIMESTAMP
+            "#,
+            session_id,
+        )
+        .fetch_optional(&mut *conn)
+        .await?;
+        Ok(user)
+    }
+
+    /// Check a username and password, returning the matching user
+    pub async fn authenticate(
+        conn: &mut PgConnection,
+        username: &str,
+        password: &str,
+    ) -> Result<Self, UserError> {
+        let maybe_user = sqlx::query!(
+            r#"SELECT
+                id as "id: Uuid",
+                username,
+                created_at as "created_at: OffsetDateTime",
+                password_hash
+            FROM users
+            WHERE username = $1
+            "#,
+            username,
+        )
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        // Verifying is as slow as hashing, so it stays off the async runtime too. Unknown
+        //  users are checked against a dummy hash, so the response time doesn't give away
+        //  which usernames exist.
+        let password_hash = maybe_user
+            .as_ref()
+            .map_or(DUMMY_PASSWORD_HASH, |user| user.password_hash.as_str());
+        let (password, password_hash) = (password.to_string(), password_hash.to_string());
+        tokio::task::spawn_blocking(move || {
+            let password_hash =
+                PasswordHash::new(&password_hash).map_err(|_| UserError::PasswordHash)?;
+            Argon2::default()
+                .verify_password(password.as_bytes(), &password_hash)
+                .map_err(|_| UserError::InvalidCredentials)
+        })
+        .await
+        .map_err(|_| UserError::PasswordHash)??;
+
+        let user = maybe_user.ok_or(UserError::InvalidCredentials)?;
+        Ok(Self {
+            id: user.id,
+            username: user.username,
+            created_at: user.created_at,
+        })
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum UserError {
+    #[error("sqlx error: {0}")]
+    Sqlx(#[from] sqlx::Error),
+    #[error("usernames must be 3 to 32 letters, numbers, dashes or underscores")]
+    InvalidUsername,
+    #[error("passwords must be at least 8 characters")]
+    WeakPassword,
+    #[error("username already taken")]
+    UsernameTaken,
+    #[error("invalid username or password")]
+    InvalidCredentials,
+    #[error("failed to hash password")]
+    PasswordHash,
+}
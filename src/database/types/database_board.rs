@@ -2,6 +2,11 @@ use std::fmt::{self, Debug, Display, Formatter};
 use std::ops::{Deref, DerefMut};
 
 use pleco::board::Board;
+use pleco::core::bitboard::BitBoard;
+use pleco::core::sq::SQ;
+use pleco::core::{Piece, PieceType, Player, Rank};
+use pleco::BitMove;
+use serde::{Serialize, Serializer};
 use sqlx::error::BoxDynError;
 use sqlx::postgres::{PgTypeInfo, PgValueRef};
 use sqlx::{Decode, Postgres, Type};
@@ -13,6 +18,233 @@ impl DatabaseBoard {
     pub fn new() -> Self {
         Self(Board::start_pos())
     }
+
+    /// The parts of the FEN that identify a position for repetition purposes -- piece
+    ///  placement, side to move, castling rights and en passant square. The halfmove
+    ///  and fullmove clocks are dropped since they differ between repeated positions.
+    pub fn repetition_key(&self) -> String {
+        let fen = self.0.fen();
+        fen.split_whitespace().take(4).collect::<Vec<_>>().join(" ")
+    }
+
+    /// Whether neither side has enough material left to deliver checkmate:
+    ///  bare kings, a single minor piece, or only bishops all on the same color
+    pub fn insufficient_material(&self) -> bool {
+        let board = &self.0;
+        let heavy_or_pawns = board.piece_bb_both_players(PieceType::P)
+            | board.piece_bb_both_players(PieceType::R)
+            | board.piece_bb_both_players(PieceType::Q);
+        if heavy_or_pawns.is_not_empty() {
+            return false;
+        }
+
+        let knights = board.piece_bb_both_players(PieceType::N);
+        let bishops = board.piece_bb_both_players(PieceType::B);
+        if (knights | bishops).count_bits() <= 1 {
+            return true;
+        }
+
+        knights.is_empty()
+            && ((bishops & BitBoard::DARK_SQUARES).is_empty()
+                || (bishops & BitBoard::LIGHT_SQUARES).is_empty())
+    }
+
+    /// Find the legal move a UCI string describes, or explain why it isn't one
+    pub fn check_move(&self, uci_move: &str) -> Result<BitMove, MoveError> {
+        let board = &self.0;
+        let (from, to, promotion) =
+            parse_uci(uci_move).ok_or_else(|| MoveError::Malformed(uci_move.to_string()))?;
+
+        let piece = board.piece_at_sq(from);
+        if piece == Piece::None {
+            return Err(MoveError::NoPiece(from.to_string()));
+        }
+        let turn = board.turn();
+        if piece.player_lossy() != turn {
+            return Err(MoveError::WrongSide(from.to_string(), player_name(turn)));
+        }
+
+        // Promotions are the only moves that share their squares
+        let candidates: Vec<BitMove> = board
+            .generate_moves()
+            .iter()
+            .copied()
+            .filter(|bit_move| bit_move.stringify()[..4] == uci_move[..4])
+            .collect();
+        let first = match candidates.first() {
+            Some(first) => *first,
+            None if self.reaches(from, to) => {
+                return Err(MoveError::LeavesKingInCheck(
+                    from.to_string(),
+                    to.to_string(),
+                ))
+            }
+            None => {
+                return Err(MoveError::CannotMoveThere(
+                    piece_name(piece.type_of()),
+                    from.to_string(),
+                    to.to_string(),
+                ))
+            }
+        };
+
+        if !first.is_promo() {
+            return match promotion {
+                Some(promotion) => Err(MoveError::UnexpectedPromotion(promotion)),
+                None => Ok(first),
+            };
+        }
+        // Strongest piece first
+        let mut options: Vec<char> = candidates
+            .iter()
+            .map(|bit_move| bit_move.promo_piece().char_lower())
+            .collect();
+        options.sort_by_key(|option| "qrbn".find(*option));
+        let options = options
+            .iter()
+            .map(|option| option.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        match promotion {
+            None => Err(MoveError::MissingPromotion(options)),
+            Some(promotion) => candidates
+                .into_iter()
+                .find(|bit_move| bit_move.promo_piece().char_lower() == promotion)
+                .ok_or(MoveError::InvalidPromotion(promotion, options)),
+        }
+    }
+
+    /// Whether the piece on one square could get to another if its own king's safety
+    ///  didn't matter
+    fn reaches(&self, from: SQ, to: SQ) -> bool {
+        let board = &self.0;
+        let piece = board.piece_at_sq(from);
+        let player = piece.player_lossy();
+        if (board.get_occupied_player(player) & to.to_bb()).is_not_empty() {
+            return false;
+        }
+        if piece.type_of() != PieceType::P {
+            return (board.attacks_from(piece.type_of(), from, player) & to.to_bb()).is_not_empty();
+        }
+
+        // Pawns capture diagonally, including en passant, and push forward onto empty squares
+        let captures = board.attacks_from(PieceType::P, from, player) & to.to_bb();
+        if captures.is_not_empty()
+            && (to == board.ep_square()
+                || (board.get_occupied_player(!player) & to.to_bb()).is_not_empty())
+        {
+            return true;
+        }
+        let one = SQ((from.0 as i8 + player.pawn_push()) as u8);
+        if board.piece_at_sq(one) != Piece::None {
+            return false;
+        }
+        let two = SQ((one.0 as i8 + player.pawn_push()) as u8);
+        to == one
+            || (to == two
+                && player.relative_rank_of_sq(from) == Rank::R2
+                && board.piece_at_sq(two) == Piece::None)
+    }
+
+    /// Every legal move on this board in UCI format, e.g. `e2e4` or `e7e8q`
+    pub fn legal_moves(&self) -> Vec<String> {
+        self.0
+            .generate_moves()
+            .iter()
+            .map(|bit_move| bit_move.stringify())
+            .collect()
+    }
+
+    /// Find the legal move that turns this board into the next one, if there is one
+    pub fn move_to(&self, next: &DatabaseBoard) -> Option<BitMove> {
+        let next_fen = next.fen();
+        self.0.generate_moves().iter().copied().find(|bit_move| {
+            let mut board = self.0.clone();
+            board.apply_move(*bit_move);
+            board.fen() == next_fen
+        })
+    }
+
+    /// Find the legal move a SAN string describes. Check and annotation marks are
+    ///  ignored, as are zeros for castling and a missing `=` on promotions.
+    pub fn san_move(&self, san: &str) -> Option<BitMove> {
+        let normalize = |san: &str| {
+            san.trim_end_matches(['+', '#', '!', '?'])
+                .replace('0', "O")
+                .replace('=', "")
+        };
+        let san = normalize(san);
+        self.0
+            .generate_moves()
+            .iter()
+            .copied()
+            .find(|bit_move| normalize(&self.san(*bit_move)) == san)
+    }
+
+    /// Describe a legal move on this board in standard algebraic notation, e.g. `Nbd7`,
+    ///  `exd5`, `O-O` or `e8=Q#`
+    pub fn san(&self, bit_move: BitMove) -> String {
+        let board = &self.0;
+        let mut san = if bit_move.is_king_castle() {
+            "O-O".to_string()
+        } else if bit_move.is_queen_castle() {
+            "O-O-O".to_string()
+        } else {
+            let src = bit_move.get_src();
+            let dest = bit_move.get_dest();
+            let piece = board.piece_at_sq(src).type_of();
+            let src_name = src.to_string();
+
+            let mut san = String::new();
+            if piece == PieceType::P {
+                if bit_move.is_capture() {
+                    san.push_str(&src_name[..1]);
+                }
+            } else {
+                san.push(piece.char_upper());
+                // Name the source file, rank, or both when another piece of the same kind
+                //  could also reach the destination
+                let rivals = board
+                    .generate_moves()
+                    .iter()
+                    .filter(|rival| {
+                        rival.get_dest() == dest
+                            && rival.get_src() != src
+                            && board.piece_at_sq(rival.get_src()).type_of() == piece
+                    })
+                    .map(|rival| rival.get_src())
+                    .collect::<Vec<_>>();
+                if !rivals.is_empty() {
+                    if rivals.iter().all(|rival| rival.file() != src.file()) {
+                        san.push_str(&src_name[..1]);
+                    } else if rivals.iter().all(|rival| rival.rank() != src.rank()) {
+                        san.push_str(&src_name[1..]);
+                    } else {
+                        san.push_str(&src_name);
+                    }
+                }
+            }
+
+            if bit_move.is_capture() {
+                san.push('x');
+            }
+            san.push_str(&dest.to_string());
+            if bit_move.is_promo() {
+                san.push('=');
+                san.push(bit_move.promo_piece().char_upper());
+            }
+            san
+        };
+
+        let mut after = board.clone();
+        after.apply_move(bit_move);
+        if after.checkmate() {
+            san.push('#');
+        } else if after.in_check() {
+            san.push('+');
+        }
+        san
+    }
 }
 
 impl Decode<'_, Postgres> for DatabaseBoard {
@@ -26,6 +258,13 @@ impl Decode<'_, Postgres> for DatabaseBoard {
     }
 }
 
+/// Boards are sent to API clients as their FEN
+impl Serialize for DatabaseBoard {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0.fen())
+    }
+}
+
 impl Type<Postgres> for DatabaseBoard {
     fn compatible(ty: &PgTypeInfo) -> bool {
         <String as Type<Postgres>>::compatible(ty)
@@ -68,8 +307,231 @@ impl From<Board> for DatabaseBoard {
     }
 }
 
+/// Split a UCI move like `e7e8q` into its squares and promotion piece, if it is one
+fn parse_uci(uci_move: &str) -> Option<(SQ, SQ, Option<char>)> {
+    let square = |name: &[u8]| match name {
+        [file @ b'a'..=b'h', rank @ b'1'..=b'8'] => Some(SQ((rank - b'1') * 8 + (file - b'a'))),
+        _ => None,
+    };
+    let bytes = uci_move.as_bytes();
+    let promotion = match bytes.get(4..) {
+        Some([]) => None,
+        Some([promotion @ (b'q' | b'r' | b'b' | b'n')]) => Some(*promotion as char),
+        _ => return None,
+    };
+    Some((square(&bytes[..2])?, square(&bytes[2..4])?, promotion))
+}
+
+fn player_name(player: Player) -> &'static str {
+    match player {
+        Player::White => "white",
+        Player::Black => "black",
+    }
+}
+
+fn piece_name(piece: PieceType) -> &'static str {
+    match piece {
+        PieceType::P => "pawn",
+        PieceType::N => "knight",
+        PieceType::B => "bishop",
+        PieceType::R => "rook",
+        PieceType::Q => "queen",
+        PieceType::K => "king",
+        _ => "piece",
+    }
+}
+
+/// Why a move can't be played on a board
+#[derive(Debug, thiserror::Error)]
+pub enum MoveError {
+    #[error("{0} isn't a move in UCI format, e.g. e2e4 or e7e8q")]
+    Malformed(String),
+    #[error("there's no piece on {0}")]
+    NoPiece(String),
+    #[error("the piece on {0} isn't {1}'s, and it's {1}'s turn")]
+    WrongSide(String, &'static str),
+    #[error("the {0} on {1} can't move to {2}")]
+    CannotMoveThere(&'static str, String, String),
+    #[error("moving from {0} to {1} would leave the king in check")]
+    LeavesKingInCheck(String, String),
+    #[error("pick a piece to promote to: one of {0}")]
+    MissingPromotion(String),
+    #[error("can't promote to {0}, pick one of {1}")]
+    InvalidPromotion(char, String),
+    #[error("only pawns reaching the last rank can promote, so {0} isn't needed")]
+    UnexpectedPromotion(char),
+}
+
+impl MoveError {
+    /// A stable, machine-readable name for the reason, for API clients
+    pub fn reason(&self) -> &'static str {
+        match self {
+            MoveError::Malformed(_) => "malformed",
+            MoveError::NoPiece(_) => "no_piece",
+            MoveError::WrongSide(_, _) => "wrong_side",
+            MoveError::CannotMoveThere(_, _, _) => "cannot_move_there",
+            MoveError::LeavesKingInCheck(_, _) => "leaves_king_in_check",
+            MoveError::MissingPromotion(_) => "missing_promotion",
+            MoveError::InvalidPromotion(_, _) => "invalid_promotion",
+            MoveError::UnexpectedPromotion(_) => "unexpected_promotion",
+        }
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum DatabaseBoardError {
     #[error("invalid fen string")]
     InvalidFen,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(fen: &str) -> DatabaseBoard {
+        DatabaseBoard::from(Board::from_fen(fen).expect("valid fen"))
+    }
+
+    /// Play UCI moves in order, returning every position along the way, the first included
+    fn play(start: DatabaseBoard, uci_moves: &[&str]) -> Vec<DatabaseBoard> {
+        let mut positions = vec![start.clone()];
+        let mut board = start;
+        for uci_move in uci_moves {
+            assert!(board.apply_uci_move(uci_move), "illegal move {}", uci_move);
+            positions.push(board.clone());
+        }
+        positions
+    }
+
+    fn san(fen: &str, uci_move: &str) -> String {
+        let board = board(fen);
+        let bit_move = board.check_move(uci_move).expect("legal move");
+        board.san(bit_move)
+    }
+
+    #[test]
+    fn repetition_key_ignores_move_clocks() {
+        let positions = play(DatabaseBoard::new(), &["g1f3", "g8f6", "f3g1", "f6g8"]);
+        let (start, end) = (&positions[0], &positions[4]);
+        assert_ne!(start.fen(), end.fen());
+        assert_eq!(start.repetition_key(), end.repetition_key());
+    }
+
+    #[test]
+    fn repetition_key_includes_castling_rights() {
+        let castling = board("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        let no_castling = board("r3k2r/8/8/8/8/8/8/R3K2R w - - 0 1");
+        assert_ne!(castling.repetition_key(), no_castling.repetition_key());
+    }
+
+    #[test]
+    fn threefold_repetition_counts_the_starting_position() {
+        let shuffle = [
+            "g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8",
+        ];
+        let positions = play(DatabaseBoard::new(), &shuffle);
+        let key = positions.last().unwrap().repetition_key();
+        let repetitions = positions
+            .iter()
+            .filter(|position| position.repetition_key() == key)
+            .count();
+        assert_eq!(repetitions, 3);
+
+        // One shuffle short, the position has only come up twice
+        let repetitions = positions[..5]
+            .iter()
+            .filter(|position| position.repetition_key() == key)
+            .count();
+        assert_eq!(repetitions, 2);
+    }
+
+    #[test]
+    fn fifty_move_rule_counts_quiet_moves() {
+        let positions = play(board("4k3/8/8/8/8/8/8/4K2R w K - 99 80"), &["h1h2"]);
+        assert_eq!(positions[1].rule_50(), 100);
+    }
+
+    #[test]
+    fn fifty_move_rule_resets_on_pawn_moves_and_captures() {
+        let positions = play(board("4k3/8/8/8/8/8/4P3/4K3 w - - 99 80"), &["e2e4"]);
+        assert_eq!(positions[1].rule_50(), 0);
+
+        let positions = play(
+            board("4k3/8/8/8/8/8/r7/4K2R w - - 99 80"),
+            &["h1h2", "a2h2"],
+        );
+        assert_eq!(positions[1].rule_50(), 100);
+        assert_eq!(positions[2].rule_50(), 0);
+    }
+
+    #[test]
+    fn insufficient_material() {
+        // Bare kings, a lone minor piece, and bishops all on one color can't mate
+        assert!(board("8/8/4k3/8/8/4K3/8/8 w - - 0 1").insufficient_material());
+        assert!(board("8/8/4k3/8/8/4K3/8/6N1 w - - 0 1").insufficient_material());
+        assert!(board("8/8/4k3/8/8/4K3/8/5B2 w - - 0 1").insufficient_material());
+        assert!(board("8/8/4k3/8/2b5/4K3/8/5B2 w - - 0 1").insufficient_material());
+
+        // Anything more could
+        assert!(!board("8/8/4k3/8/8/4K3/8/2B2B2 w - - 0 1").insufficient_material());
+        assert!(!board("8/8/4k3/8/8/4K3/8/5BN1 w - - 0 1").insufficient_material());
+        assert!(!board("8/8/4k3/8/8/4K3/8/1N4N1 w - - 0 1").insufficient_material());
+        assert!(!board("8/8/4k3/8/8/4K3/4P3/8 w - - 0 1").insufficient_material());
+        assert!(!board("8/8/4k3/8/8/4K3/8/7R w - - 0 1").insufficient_material());
+        assert!(!DatabaseBoard::new().insufficient_material());
+    }
+
+    #[test]
+    fn san_move_finds_moves() {
+        let start = DatabaseBoard::new();
+        let found = |board: &DatabaseBoard, san| board.san_move(san).map(|m| m.stringify());
+        assert_eq!(found(&start, "e4").as_deref(), Some("e2e4"));
+        assert_eq!(found(&start, "Nf3").as_deref(), Some("g1f3"));
+        assert_eq!(found(&start, "e5"), None);
+        assert_eq!(found(&start, "Ke2"), None);
+    }
+
+    #[test]
+    fn san_move_is_lenient_about_marks() {
+        let castling = board("4k3/8/8/8/8/8/8/4K2R w K - 0 1");
+        let promotion = board("4k3/P7/8/8/8/8/8/4K3 w - - 0 1");
+        let found = |board: &DatabaseBoard, san| board.san_move(san).map(|m| m.stringify());
+        assert_eq!(
+            found(&DatabaseBoard::new(), "e4!?").as_deref(),
+            Some("e2e4")
+        );
+        assert_eq!(found(&castling, "O-O").as_deref(), Some("e1g1"));
+        assert_eq!(found(&castling, "0-0").as_deref(), Some("e1g1"));
+        assert_eq!(found(&promotion, "a8=Q+").as_deref(), Some("a7a8q"));
+        assert_eq!(found(&promotion, "a8N").as_deref(), Some("a7a8n"));
+    }
+
+    #[test]
+    fn san_names_pieces_captures_and_castling() {
+        let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        assert_eq!(san(start, "e2e4"), "e4");
+        assert_eq!(san(start, "g1f3"), "Nf3");
+        let scandinavian = "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2";
+        assert_eq!(san(scandinavian, "e4d5"), "exd5");
+        let castling = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+        assert_eq!(san(castling, "e1g1"), "O-O");
+        assert_eq!(san(castling, "e1c1"), "O-O-O");
+    }
+
+    #[test]
+    fn san_disambiguates_rival_pieces() {
+        // By file when the rivals are on different files, by rank when they share one
+        assert_eq!(san("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1", "b1d2"), "Nbd2");
+        assert_eq!(san("4k3/8/8/R7/8/8/8/R3K3 w - - 0 1", "a1a3"), "R1a3");
+        // By both when neither alone would do
+        assert_eq!(san("1k6/8/8/8/4Q2Q/8/8/K6Q w - - 0 1", "h4e1"), "Qh4e1");
+    }
+
+    #[test]
+    fn san_marks_promotions_checks_and_mates() {
+        assert_eq!(san("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7a8q"), "a8=Q+");
+        assert_eq!(san("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7a8n"), "a8=N");
+        let fools_mate = "rnbqkbnr/pppp1ppp/8/4p3/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq g3 0 2";
+        assert_eq!(san(fools_mate, "d8h4"), "Qh4#");
+    }
+}
@@ -1,3 +1,3 @@
 mod database_board;
 
-pub use database_board::DatabaseBoard;
+pub use database_board::{DatabaseBoard, MoveError};
@@ -1,14 +1,26 @@
+use std::time::Duration;
+
 use askama::Template;
 use axum::Extension;
-use axum::{response::IntoResponse, routing::get, Router};
+use axum::{
+    response::IntoResponse,
+    routing::{get, post},
+    Router,
+};
 use sqlx::PgPool;
-use tokio::sync::broadcast::channel;
 use tower_http::services::ServeDir;
 
 mod api;
 mod database;
 
-use api::templates::GameBoardTemplate;
+use api::games::watch_game_sse::GameUpdateStream;
+use api::session::CurrentUser;
+use database::models::{GameMove, MAX_BOT_DEPTH, MIN_BOT_DEPTH};
+
+/// Hours a game can sit without a move before it's abandoned, unless configured otherwise
+const DEFAULT_ABANDON_AFTER_HOURS: u64 = 7 * 24;
+/// Hours a move request's idempotency key is kept, unless configured otherwise
+const DEFAULT_IDEMPOTENCY_KEY_TTL_HOURS: u64 = 24;
 
 #[derive(Clone)]
 pub struct AppState {
@@ -37,9 +49,26 @@ async fn main(
         .run(&db)
         .await
         .expect("Looks like something went wrong with migrations :(");
+    // Fill in the notation of moves recorded before it was stored
+    match db.acquire().await {
+        Ok(mut conn) => match GameMove::backfill(&mut conn).await {
+            Ok(filled) => tracing::info!("backfilled notation for {} moves", filled),
+            Err(e) => tracing::error!("failed to backfill move notation: {}", e),
+        },
+        Err(e) => tracing::error!("failed to backfill move notation: {}", e),
+    }
     // Setup State
     let state = AppState::new(db);
-    let (tx, _rx) = channel::<GameBoardTemplate>(10);
+    let game_updates = GameUpdateStream::new();
+
+    // End games on the clock when a player runs out of time
+    api::clocks::spawn_flagger(state.clone(), game_updates.clone());
+    // Abandon games nobody has played on for a while
+    api::abandonment::spawn_sweeper(state.clone(), game_updates.clone(), abandon_after());
+    // Forget idempotency keys once clients are done retrying with them
+    api::idempotency::spawn_expirer(state.clone(), idempotency_key_ttl());
+    // Clear out login sessions once they expire
+    api::session::spawn_expirer(state.clone());
 
     // Register panics as they happen
     register_panic_logger();
@@ -49,20 +78,54 @@ async fn main(
     let router = Router::new()
         // Home page
         .route("/", get(index))
+        // Accounts
+        .route(
+            "/login",
+            get(api::users::read_login::handler).post(api::users::login::handler),
+        )
+        .route("/logout", post(api::users::logout::handler))
+        .route("/register", post(api::users::register::handler))
         .route(
             "/games",
             get(api::games::read_all_games::handler).post(api::games::create_game::handler),
         )
+        .route("/games/import", post(api::games::import_games::handler))
         .route(
             "/games/:game_id",
             get(api::games::read_game::handler).post(api::games::make_move::handler),
         )
+        .route(
+            "/games/:game_id/seats/:player",
+            post(api::games::claim_seat::handler),
+        )
+        .route(
+            "/games/:game_id/join/:token",
+            get(api::games::join_seat::handler),
+        )
+        .route(
+            "/games/:game_id/draw",
+            post(api::games::draw_offer::handler),
+        )
+        .route(
+            "/games/:game_id/takeback",
+            post(api::games::takeback::handler),
+        )
+        .route(
+            "/games/:game_id/pgn",
+            get(api::games::read_game_pgn::handler),
+        )
+        .route(
+            "/games/:game_id/positions/:ply",
+            get(api::games::read_position::handler),
+        )
         .route(
             "/games/:game_id/sse",
             get(api::games::watch_game_sse::handler),
         )
+        // JSON API
+        .nest("/api/v1", api::v1::router())
         .with_state(state)
-        .layer(Extension(tx))
+        .layer(Extension(game_updates))
         // Static assets
         .nest_service("/static", ServeDir::new("static"));
 
@@ -70,13 +133,39 @@ async fn main(
     Ok(router.into())
 }
 
-async fn index() -> impl IntoResponse {
-    IndexTemplate
+async fn index(user: Option<CurrentUser>) -> impl IntoResponse {
+    IndexTemplate {
+        username: user.map(|CurrentUser(user)| user.username().to_string()),
+        bot_depths: (MIN_BOT_DEPTH..=MAX_BOT_DEPTH).collect(),
+    }
 }
 
 #[derive(Template)]
 #[template(path = "index.html")]
-struct IndexTemplate;
+struct IndexTemplate {
+    username: Option<String>,
+    bot_depths: Vec<i16>,
+}
+
+/// How long a game can sit without a move before it's abandoned, configured in hours with
+///  `ABANDON_AFTER_HOURS` -- a week by default
+fn abandon_after() -> Duration {
+    let hours = std::env::var("ABANDON_AFTER_HOURS")
+        .ok()
+        .and_then(|hours| hours.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_ABANDON_AFTER_HOURS);
+    Duration::from_secs(hours * 60 * 60)
+}
+
+/// How long a move request's idempotency key is kept, configured in hours with
+///  `IDEMPOTENCY_KEY_TTL_HOURS` -- a day by default
+fn idempotency_key_ttl() -> Duration {
+    let hours = std::env::var("IDEMPOTENCY_KEY_TTL_HOURS")
+        .ok()
+        .and_then(|hours| hours.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_IDEMPOTENCY_KEY_TTL_HOURS);
+    Duration::from_secs(hours * 60 * 60)
+}
 
 /// Sets up system panics to use the tracing infrastructure to log reported issues. This doesn't
 /// prevent the panic from taking out the service but ensures that it and any available information
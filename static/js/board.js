@@ -8,6 +8,19 @@ function squareHasPiece(square) {
     return square.innerHTML !== '';
 }
 
+// The squares the piece on a square can legally move to, as listed by the server
+function legalDestinations(square) {
+    let legalMoves = square.dataset.legalMoves;
+    return legalMoves ? legalMoves.split(' ') : [];
+}
+
+// Turn the highlighting of a piece's legal destinations on or off
+function highlightDestinations(square, on) {
+    legalDestinations(square).forEach(id => {
+        document.getElementById(id).classList.toggle('legal-target', on);
+    });
+}
+
 // Logic for moving a piece
 function movePiece(fromSquare, toSquare) {
     // Get the identifying class name (e.g. `chess-piece-P` or `chess-piece-p`) of the piece
@@ -23,17 +36,12 @@ function movePiece(fromSquare, toSquare) {
     let promotionHtml = null;
     let promotionClass = null;
     uciMove = `${fromPosition}${toPosition}`;
-    // Check if a pawn is being promoted 
-    if ((fromPiece === 'P' && toRank === '8') || (fromPiece === 'p' && toRank === '1')) {
-        // TODO: allow user to select piece to promote to piece of their choice
-        uciMove += 'q'; // Promote to queen 
-        if (fromPiece === 'P') {
-            promotionHtml = '♕';
-            promotionClass = 'chess-piece-Q';
-        } else {
-            promotionHtml = '♛';
-            promotionClass = 'chess-piece-q';
-        }
+    // Check if a pawn is being promoted, and let the player pick the piece (queen by default)
+    let promoting = (fromPiece === 'P' && toRank === '8') || (fromPiece === 'p' && toRank === '1');
+    if (promoting) {
+        let promotion = document.getElementById('promotionSelect').value;
+        uciMove += promotion;
+        [promotionHtml, promotionClass] = promotionPiece(fromPiece, promotion);
     }
 
     // Update the board
@@ -47,12 +55,42 @@ function movePiece(fromSquare, toSquare) {
     }
     fromSquare.innerHTML = ''; // Remove the piece from the current square
     sendMove(uciMove); 
+    document.getElementById('promotionChoice').style.display = promoting ? 'inline' : 'none';
+}
+
+// The html and class of the piece a pawn promotes to, in the pawn's color
+function promotionPiece(pawn, promotion) {
+    const pieces = {
+        q: ['♕', '♛'],
+        r: ['♖', '♜'],
+        b: ['♗', '♝'],
+        n: ['♘', '♞'],
+    };
+    let white = pawn === 'P';
+    let html = pieces[promotion][white ? 0 : 1];
+    let pieceClass = `chess-piece-${white ? promotion.toUpperCase() : promotion}`;
+    return [html, pieceClass];
+}
+
+// Swap the promotion piece on a pending move when the player picks a different one
+function changePromotion(promotion) {
+    let uciMoveInput = document.getElementById('uciMoveInput');
+    if (!toSquare || uciMoveInput.value.length !== 5) return;
+    uciMoveInput.value = uciMoveInput.value.slice(0, 4) + promotion;
+    let pawn = toSquare.getAttribute('id')[1] === '8' ? 'P' : 'p';
+    let [html, pieceClass] = promotionPiece(pawn, promotion);
+    toSquare.innerHTML = html;
+    toSquare.classList.replace(toSquare.classList[1], pieceClass);
 }
 
 function sendMove(uciMove) {
     console.log(uciMove);
     // Write our move to the hidden input field
     document.getElementById('uciMoveInput').value = uciMove;
+    // Each move gets its own key, which stays the same however many times it's submitted
+    document.getElementById('idempotencyKeyInput').value = crypto.randomUUID();
+    // A new move gets a fresh hearing
+    document.getElementById('moveError').textContent = '';
     // Make the button visible
     document.getElementById('moveForm').style.display = 'block';
 }
@@ -67,8 +105,13 @@ initBoard = function() {
         square.replaceWith(square.cloneNode(true));
     });
 
-    document.getElementById('moveForm').style.display = 'none';
-    document.getElementById('uciMoveInput').value = '';
+    // Spectators and players waiting on their opponent don't get a move form
+    const moveForm = document.getElementById('moveForm');
+    if (moveForm) {
+        moveForm.style.display = 'none';
+        document.getElementById('uciMoveInput').value = '';
+        document.getElementById('promotionChoice').style.display = 'none';
+    }
 
     // Assuming 'chessboard' is the ID of the parent element
     const chessboard = document.getElementById('chessboard');
@@ -76,20 +119,40 @@ initBoard = function() {
         // Check if the clicked element is a chess square
         const clickedSquare = event.target.closest('[class*="chess-square-"]');
         if (!clickedSquare) return; // Not a chess square, ignore the click
+        if (!document.getElementById('moveForm')) return; // Not our turn to move
         if (!selectedPiece && squareHasPiece(clickedSquare)) {
+            // Only pieces with somewhere to go can be picked up
+            if (legalDestinations(clickedSquare).length === 0) return;
             // Select the piece
             selectedPiece = clickedSquare;
             fromSquare = clickedSquare;
             clickedSquare.classList.add('selected');
+            highlightDestinations(clickedSquare, true);
         } else if (selectedPiece) {
             if (clickedSquare === selectedPiece) {
                 // Deselect the piece
                 clickedSquare.classList.remove('selected');
+                highlightDestinations(clickedSquare, false);
                 selectedPiece = null;
                 return;
 
             }
 
+            if (!legalDestinations(selectedPiece).includes(clickedSquare.id)) {
+                // Refuse illegal drops, but let the player switch to another movable piece
+                if (legalDestinations(clickedSquare).length > 0) {
+                    selectedPiece.classList.remove('selected');
+                    highlightDestinations(selectedPiece, false);
+                    selectedPiece = clickedSquare;
+                    fromSquare = clickedSquare;
+                    clickedSquare.classList.add('selected');
+                    highlightDestinations(clickedSquare, true);
+                }
+                return;
+            }
+
+            // Clear the highlights first, since movePiece reads the squares' classes
+            highlightDestinations(selectedPiece, false);
             toSquare = clickedSquare;
             // Move the piece to the new square
             movePiece(selectedPiece, clickedSquare);
@@ -105,6 +168,8 @@ initBoard = function() {
             let fromSqaureHtml = fromSquare.innerHTML;
             fromSquare.innerHTML = toSquare.innerHTML;
             toSquare.innerHTML = fromSqaureHtml;
+            // Say why the move was turned down
+            document.getElementById('moveError').textContent = event.detail.xhr.responseText;
         }
     });
 }
@@ -0,0 +1,30 @@
+// Count down the running clock between server updates. The server sends each side's
+//  remaining time with every board, so we only need to tick from when it arrived.
+
+// Keep this in sync with `ApiClock::remaining`
+function formatClock(remainingMs) {
+    let totalSeconds = Math.floor(Math.max(remainingMs, 0) / 1000);
+    let days = Math.floor(totalSeconds / 86400);
+    let hours = Math.floor(totalSeconds / 3600) % 24;
+    let minutes = Math.floor(totalSeconds / 60) % 60;
+    let seconds = totalSeconds % 60;
+    let pad = (n) => String(n).padStart(2, '0');
+    if (days > 0) {
+        return `${days}d ${pad(hours)}:${pad(minutes)}:${pad(seconds)}`;
+    } else if (hours > 0) {
+        return `${hours}:${pad(minutes)}:${pad(seconds)}`;
+    }
+    return `${minutes}:${pad(seconds)}`;
+}
+
+setInterval(function() {
+    document.querySelectorAll('.clock[data-running="true"]').forEach(clock => {
+        // Boards swapped in by the stream bring fresh clocks, so start ticking from then
+        if (!clock.dataset.receivedAt) {
+            clock.dataset.receivedAt = Date.now();
+        }
+        let elapsed = Date.now() - Number(clock.dataset.receivedAt);
+        let remainingMs = Number(clock.dataset.remainingMs) - elapsed;
+        clock.querySelector('.clock-time').textContent = formatClock(remainingMs);
+    });
+}, 200);